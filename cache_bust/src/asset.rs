@@ -0,0 +1,70 @@
+use std::{ffi::OsString, path::PathBuf};
+
+use cache_bust_core::HashAlgorithm;
+
+use crate::{manifest::to_url_path, CompressedAsset};
+
+/// A file that was hashed by [CacheBust][crate::CacheBust]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct HashedAsset {
+	/// The path of the original file
+	pub source: PathBuf,
+	/// The path of the hashed file, either in `out_dir` or next to the original file if `in_place` is set
+	pub dest: PathBuf,
+	/// The path of the original file relative to `in_dir`
+	pub relative_path: PathBuf,
+	/// The path of the hashed file relative to `out_dir`, or relative to `in_dir` if `in_place` is set
+	pub hashed_relative_path: PathBuf,
+	/// The hashed name as produced by the [NameTemplate][crate::NameTemplate], which includes
+	/// the query string if the template has one
+	pub hashed_name: OsString,
+	/// The encoded hash of the file's contents
+	pub hash: String,
+	/// The algorithm used to hash the file
+	pub algorithm: HashAlgorithm,
+	/// The Subresource Integrity string of the hashed file, like `sha384-oqVu[...]`, if an
+	/// [integrity algorithm][crate::CacheBustBuilder::integrity] is set
+	pub integrity: Option<String>,
+	/// The size of the file in bytes
+	pub len: u64,
+	/// Whether the hashed file was written or already up to date
+	pub status: AssetStatus,
+	/// The precompressed variants written next to the hashed file
+	pub compressed: Vec<CompressedAsset>,
+}
+
+/// Whether a [HashedAsset] was written by [CacheBust][crate::CacheBust]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AssetStatus {
+	/// The hashed file was copied or moved to its destination.
+	Written,
+	/// The hashed file already existed in `out_dir`, so it wasn't copied again.
+	/// This only happens if `incremental` is set.
+	Unchanged,
+}
+
+/// The result of [hash_dir][crate::CacheBust::hash_dir]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct HashDirReport {
+	/// The hashed files
+	pub assets: Vec<HashedAsset>,
+	/// The stale files which were removed from `out_dir`. This is only
+	/// filled if `incremental` is set, otherwise `out_dir` is cleared entirely.
+	pub removed: Vec<PathBuf>,
+}
+
+impl HashDirReport {
+	/// Returns an iterator over the hashed files that were written, excluding ones that were unchanged.
+	pub fn written(&self) -> impl Iterator<Item = &HashedAsset> {
+		self.assets.iter().filter(|asset| asset.status == AssetStatus::Written)
+	}
+}
+
+impl HashedAsset {
+	/// Returns the path to reference the hashed file by, relative to `out_dir` and using `/` as the
+	/// separator. Unlike `hashed_relative_path` this includes the query string if the
+	/// [NameTemplate][crate::NameTemplate] has one.
+	pub fn url_path(&self) -> String {
+		to_url_path(&self.relative_path.with_file_name(&self.hashed_name))
+	}
+}
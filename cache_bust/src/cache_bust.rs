@@ -1,8 +1,19 @@
-use std::{error::Error, fmt::{self, Display}, fs, io, path::{Path, PathBuf}};
+use std::{collections::{HashMap, HashSet}, error::Error, fmt::{self, Display}, io::Read, ops::Range, path::{Path, PathBuf}, sync::{Mutex, PoisonError}};
 
-use cache_bust_core::hashed_file_name;
+use cache_bust_core::{parse_hashed_name_with, ConfigError, ContentHash, HashOptions, HashedName, Hasher};
+pub use cache_bust_core::{CacheBustError, Operation};
+pub use cache_bust_core::{HashAlgorithm, HashEncoding, IntegrityAlgorithm, NameTemplate, ParsedName, DEFAULT_COMPOUND_EXTENSIONS, MIN_HASH_LENGTH};
+pub use cache_bust_core::{COMPOUND_EXTENSIONS_VAR, ENV_VARS, HASH_ALGORITHM_VAR, HASH_ENCODING_VAR, HASH_LENGTH_VAR, INTEGRITY_VAR, NAME_TEMPLATE_VAR};
 use walkdir::WalkDir;
 
+use crate::{filter::{FileAction, FileFilter}, fs::{self, create_parent_dir, walk_dir_error}, graph, js::is_js_module, manifest::to_url_path, reference::{append_suffix, is_path_relative, relative_url, replace_references, resolve_url, Document, Syntax}, source_map::{find_source_mapping_url, has_source_map_comment, remove_source_mapping_comment, rewrite_source_map, SourceMapFile}, AssetStatus, CompressedAsset, Compression, ExcludePolicy, HashDirReport, HashedAsset, Manifest, SourceMapPolicy, DEFAULT_PRECOMPRESS_EXTENSIONS, DEFAULT_PRECOMPRESS_MIN_SAVINGS, DEFAULT_PRECOMPRESS_MIN_SIZE};
+
+/// Name of the marker file written into every `out_dir` by [hash_dir][CacheBust::hash_dir] and [hash_file][CacheBust::hash_file].
+/// A non-empty `out_dir` is only cleared by [CacheBust::hash_dir] if it contains this file, unless `force` is set.
+pub const OUT_DIR_MARKER: &str = ".cache_bust";
+
+const OUT_DIR_MARKER_CONTENTS: &str = "This directory is managed by cache_bust, its contents may be deleted.\n";
+
 fn warn_prefix(is_build_script: bool) -> &'static str {
 	if is_build_script {
 		"cargo::warn="
@@ -24,6 +35,36 @@ pub enum CacheBustBuilderError {
 	OutDirNotSet,
 	/// `out_dir` is a file.
 	OutDirIsAFile(PathBuf),
+	/// The hashing options are invalid, either because of an invalid environment
+	/// variable or an invalid `hash_length`.
+	InvalidHashOptions(ConfigError),
+	/// `lookup_module` is a relative path, but the `OUT_DIR` environment
+	/// variable it is relative to wasn't set.
+	LookupModuleOutDirNotSet(PathBuf),
+	/// One of the `include` or `exclude` patterns isn't a valid glob.
+	InvalidGlob(globset::Error),
+	/// `out_dir` is the same as `in_dir` or inside of it, so hashing the files would delete
+	/// some of them and hash the output again.
+	OutDirInsideInDir {
+		/// The canonicalized `in_dir`
+		in_dir: PathBuf,
+		/// The canonicalized `out_dir`
+		out_dir: PathBuf,
+	},
+	/// `in_dir` is inside `out_dir`, so clearing `out_dir` would delete the files to hash.
+	InDirInsideOutDir {
+		/// The canonicalized `in_dir`
+		in_dir: PathBuf,
+		/// The canonicalized `out_dir`
+		out_dir: PathBuf,
+	},
+	/// The level given for a [Compression] is higher than its [max_level][Compression::max_level].
+	InvalidCompressionLevel {
+		/// The compression algorithm
+		compression: Compression,
+		/// The given level
+		level: u32,
+	},
 }
 
 impl Display for CacheBustBuilderError {
@@ -34,12 +75,24 @@ impl Display for CacheBustBuilderError {
 			InDirNotADirectory(in_dir) => write!(f, "{in_dir:?} is not a directory"),
 			OutDirNotSet => write!(f, "out_dir must be specified or in_place set to true"),
 			OutDirIsAFile(out_dir) => write!(f, "{out_dir:?} is already a file"),
+			InvalidHashOptions(err) => write!(f, "{err}"),
+			LookupModuleOutDirNotSet(path) => write!(f, "lookup_module {path:?} is relative, but OUT_DIR is not set"),
+			InvalidGlob(err) => write!(f, "{err}"),
+			OutDirInsideInDir {in_dir, out_dir} => write!(f, "out_dir {out_dir:?} is inside in_dir {in_dir:?}"),
+			InDirInsideOutDir {in_dir, out_dir} => write!(f, "in_dir {in_dir:?} is inside out_dir {out_dir:?}"),
+			InvalidCompressionLevel {compression, level} => write!(f, "{compression} level {level} is invalid, it must be at most {}", compression.max_level()),
 		}
 	}
 }
 
 impl Error for CacheBustBuilderError {}
 
+impl From<CacheBustBuilderError> for CacheBustError {
+	fn from(err: CacheBustBuilderError) -> Self {
+		CacheBustError::Build(Box::new(err))
+	}
+}
+
 /// Builder for [CacheBust]
 #[derive(Clone, Debug)]
 pub struct CacheBustBuilder {
@@ -48,6 +101,28 @@ pub struct CacheBustBuilder {
 	in_place: bool,
 	is_build_script: bool,
 	enable_logging: bool,
+	hash_algorithm: Option<HashAlgorithm>,
+	hash_encoding: Option<HashEncoding>,
+	hash_length: Option<usize>,
+	name_template: Option<NameTemplate>,
+	compound_extensions: Option<Vec<String>>,
+	integrity: Option<IntegrityAlgorithm>,
+	manifest: Option<PathBuf>,
+	lookup_module: Option<PathBuf>,
+	import_map: Option<PathBuf>,
+	import_map_base: Option<String>,
+	include: Vec<String>,
+	exclude: Vec<(String, ExcludePolicy)>,
+	incremental: bool,
+	force: bool,
+	rewrite_css: bool,
+	rewrite_js: bool,
+	source_maps: SourceMapPolicy,
+	entries: Vec<String>,
+	precompress: Vec<(Compression, u32)>,
+	precompress_extensions: Option<Vec<String>>,
+	precompress_min_size: Option<u64>,
+	precompress_min_savings: Option<u8>,
 }
 
 impl Default for CacheBustBuilder {
@@ -67,6 +142,28 @@ impl Default for CacheBustBuilder {
 			in_place: false,
 			is_build_script,
 			enable_logging: true,
+			hash_algorithm: None,
+			hash_encoding: None,
+			hash_length: None,
+			name_template: None,
+			compound_extensions: None,
+			integrity: None,
+			manifest: None,
+			lookup_module: None,
+			import_map: None,
+			import_map_base: None,
+			include: Vec::new(),
+			exclude: Vec::new(),
+			incremental: false,
+			force: false,
+			rewrite_css: false,
+			rewrite_js: false,
+			source_maps: SourceMapPolicy::default(),
+			entries: Vec::new(),
+			precompress: Vec::new(),
+			precompress_extensions: None,
+			precompress_min_size: None,
+			precompress_min_savings: None,
 		}
 	}
 }
@@ -91,7 +188,8 @@ impl CacheBustBuilder {
 	}
 	
 	/// Specifies whether to rename the files in-place instead of copying them
-	/// to a new directory.
+	/// to a new directory. Files whose names already contain the hash of their
+	/// contents are left alone, so hashing a directory in-place again has no effect.
 	/// 
 	/// # Default
 	/// 
@@ -102,7 +200,8 @@ impl CacheBustBuilder {
 	}
 	
 	/// Specifies whether the executing context is a `build.rs` build script.
-	/// If so, the proper `cargo::rerun-if-changed` instructions will be emitted.
+	/// If so, the proper `cargo::rerun-if-changed` instructions will be emitted, along with a
+	/// `cargo::rerun-if-env-changed` instruction for every environment variable read when building.
 	/// 
 	/// # Default
 	/// 
@@ -122,6 +221,340 @@ impl CacheBustBuilder {
 		self
 	}
 	
+	/// Specifies the algorithm used to hash the files. This has to match the
+	/// algorithm used by the `asset!` macro, so prefer setting the
+	/// `CACHE_BUST_HASH_ALGORITHM` environment variable which is read by both.
+	/// 
+	/// # Default
+	/// 
+	/// The value of `CACHE_BUST_HASH_ALGORITHM` if set, [HashAlgorithm::Sha256] otherwise
+	pub fn hash_algorithm(mut self, algorithm: HashAlgorithm) -> Self {
+		self.hash_algorithm = Some(algorithm);
+		self
+	}
+	
+	/// Specifies the encoding used to turn the hashes into strings. Like
+	/// [hash_algorithm][CacheBustBuilder::hash_algorithm] this has to match the `asset!` macro,
+	/// which reads the `CACHE_BUST_HASH_ENCODING` environment variable.
+	/// 
+	/// # Default
+	/// 
+	/// The value of `CACHE_BUST_HASH_ENCODING` if set, [HashEncoding::Hex] otherwise
+	pub fn hash_encoding(mut self, encoding: HashEncoding) -> Self {
+		self.hash_encoding = Some(encoding);
+		self
+	}
+	
+	/// Specifies the number of characters the hashes are truncated to, which must be at
+	/// least [MIN_HASH_LENGTH]. Like [hash_algorithm][CacheBustBuilder::hash_algorithm]
+	/// this has to match the `asset!` macro, which reads the `CACHE_BUST_HASH_LENGTH`
+	/// environment variable.
+	/// 
+	/// # Default
+	/// 
+	/// The value of `CACHE_BUST_HASH_LENGTH` if set, otherwise the hashes aren't truncated
+	pub fn hash_length(mut self, length: usize) -> Self {
+		self.hash_length = Some(length);
+		self
+	}
+	
+	/// Specifies the template used to add the hashes to file names. Like
+	/// [hash_algorithm][CacheBustBuilder::hash_algorithm] this has to match the `asset!` macro,
+	/// which reads the `CACHE_BUST_NAME_TEMPLATE` environment variable.
+	/// 
+	/// # Default
+	/// 
+	/// The value of `CACHE_BUST_NAME_TEMPLATE` if set, `{stem}.{hash}.{ext}` otherwise
+	pub fn name_template(mut self, template: NameTemplate) -> Self {
+		self.name_template = Some(template);
+		self
+	}
+	
+	/// Specifies extensions consisting of multiple parts, like `min.js` or `js.map`, which are
+	/// kept together when adding the hashes to file names. Like [hash_algorithm][CacheBustBuilder::hash_algorithm]
+	/// this has to match the `asset!` macro, which reads the comma-separated
+	/// `CACHE_BUST_COMPOUND_EXTENSIONS` environment variable.
+	/// 
+	/// # Default
+	/// 
+	/// The value of `CACHE_BUST_COMPOUND_EXTENSIONS` if set, [DEFAULT_COMPOUND_EXTENSIONS] otherwise
+	pub fn compound_extensions(mut self, extensions: impl IntoIterator<Item = impl Into<String>>) -> Self {
+		self.compound_extensions = Some(extensions.into_iter().map(Into::into).collect());
+		self
+	}
+	
+	/// Specifies the algorithm used to compute a [Subresource Integrity] string of each file, which is
+	/// exposed as [HashedAsset::integrity] and written to the [Manifest]. The string is computed while
+	/// hashing the file, so the file isn't read twice.
+	/// 
+	/// [Subresource Integrity]: https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity
+	/// 
+	/// # Default
+	/// 
+	/// The value of `CACHE_BUST_INTEGRITY` if set, otherwise no integrity strings are computed
+	pub fn integrity(mut self, algorithm: IntegrityAlgorithm) -> Self {
+		self.integrity = Some(algorithm);
+		self
+	}
+	
+	/// Specifies a path to write a JSON [Manifest] to when calling [hash_dir][CacheBust::hash_dir],
+	/// mapping the original path of each file to its hashed path.
+	/// 
+	/// The manifest shouldn't be written into `in_dir` when using `in_place`,
+	/// as it would be hashed as well the next time.
+	/// 
+	/// # Default
+	/// 
+	/// No manifest is written.
+	pub fn manifest(mut self, path: impl Into<PathBuf>) -> Self {
+		self.manifest = Some(path.into());
+		self
+	}
+	
+	/// Specifies a path to write a Rust source file to when calling [hash_dir][CacheBust::hash_dir] or
+	/// [hash_file][CacheBust::hash_file], declaring a `pub static ASSETS: cache_bust::AssetMap` which maps
+	/// the original path of each file to its hashed path, and a `pub fn lookup(path: &str) -> Option<&'static str>`
+	/// function calling `ASSETS.lookup(path)`. The file can be included with `include!` to look up hashed paths at runtime.
+	/// 
+	/// The file is rewritten after every call and contains all files hashed by the [CacheBust] so far.
+	/// 
+	/// A relative path is relative to the `OUT_DIR` environment variable set by cargo for build scripts.
+	/// 
+	/// # Default
+	/// 
+	/// No lookup module is written.
+	pub fn lookup_module(mut self, path: impl Into<PathBuf>) -> Self {
+		self.lookup_module = Some(path.into());
+		self
+	}
+	
+	/// Specifies a path to write an [ImportMap][crate::ImportMap] to when calling [hash_dir][CacheBust::hash_dir], mapping
+	/// the URL of each JavaScript module to its hashed URL. This lets modules import each other by their
+	/// original names while staying unchanged, as an alternative to [rewrite_js][CacheBustBuilder::rewrite_js].
+	/// 
+	/// If `path` ends in `.html` or `.htm` the import map is written as a `<script type="importmap">`
+	/// element to be included in HTML, otherwise as JSON.
+	/// 
+	/// # Default
+	/// 
+	/// No import map is written.
+	pub fn import_map(mut self, path: impl Into<PathBuf>) -> Self {
+		self.import_map = Some(path.into());
+		self
+	}
+	
+	/// Specifies the URL `out_dir` is served at, which the URLs in the [import map][CacheBustBuilder::import_map]
+	/// start with, like `/assets/`.
+	/// 
+	/// # Default
+	/// 
+	/// `/`
+	pub fn import_map_base(mut self, base: impl Into<String>) -> Self {
+		self.import_map_base = Some(base.into());
+		self
+	}
+	
+	/// Adds a glob pattern for files to hash when calling [hash_dir][CacheBust::hash_dir]. If any
+	/// patterns are added, files not matching one of them are skipped.
+	/// 
+	/// Patterns are matched against the path relative to `in_dir` using `/` as the separator.
+	/// `*` doesn't match `/`, so use `**/*.js` to match files in any directory.
+	/// 
+	/// # Default
+	/// 
+	/// All files are hashed.
+	pub fn include(mut self, pattern: impl Into<String>) -> Self {
+		self.include.push(pattern.into());
+		self
+	}
+	
+	/// Adds a glob pattern for files to skip when calling [hash_dir][CacheBust::hash_dir], like
+	/// `**/.DS_Store`. This takes precedence over `include`.
+	/// 
+	/// Patterns are matched the same way as for [include][CacheBustBuilder::include].
+	pub fn exclude(self, pattern: impl Into<String>) -> Self {
+		self.exclude_with(pattern, ExcludePolicy::Skip)
+	}
+	
+	/// Adds a glob pattern for files which aren't hashed when calling [hash_dir][CacheBust::hash_dir],
+	/// with the given [ExcludePolicy] deciding whether they are skipped or copied under their original names,
+	/// like `index.html` or `robots.txt`. If multiple patterns match a file, the one added first is used.
+	pub fn exclude_with(mut self, pattern: impl Into<String>, policy: ExcludePolicy) -> Self {
+		self.exclude.push((pattern.into(), policy));
+		self
+	}
+	
+	/// Specifies whether [hash_dir][CacheBust::hash_dir] updates `out_dir` incrementally instead of
+	/// clearing it and copying every file again. Files whose hashed destination already exists with the
+	/// same contents are skipped and files that are no longer produced are removed. This option has no effect if
+	/// `in_place` is set.
+	/// 
+	/// # Default
+	/// 
+	/// `false`
+	pub fn incremental(mut self, incremental: bool) -> Self {
+		self.incremental = incremental;
+		self
+	}
+	
+	/// Specifies whether [hash_dir][CacheBust::hash_dir] clears `out_dir` even if it isn't empty
+	/// and doesn't contain the [OUT_DIR_MARKER] file written by cache_bust. Without this option
+	/// pointing `out_dir` at an existing directory by mistake results in a
+	/// [CacheBustError::OutDirNotOwned] error instead of deleting its contents.
+	/// 
+	/// # Default
+	/// 
+	/// `false`
+	pub fn force(mut self, force: bool) -> Self {
+		self.force = force;
+		self
+	}
+	
+	/// Specifies whether [hash_dir][CacheBust::hash_dir] rewrites references to other files in CSS files,
+	/// i.e. `url()` functions and `@import` rules, to point to their hashed names. References relative to
+	/// the CSS file and ones starting with `/`, which are relative to `in_dir`, are rewritten, while URLs
+	/// with a scheme like `https:` or `data:` and references to files that aren't hashed are left as they are.
+	/// 
+	/// Since rewriting changes the contents of the CSS files, they are hashed after the files they reference,
+	/// including other CSS files, so a changed file also changes the hashes of all files referencing it.
+	/// If files reference each other in a cycle, [hash_dir][CacheBust::hash_dir] fails with
+	/// [CacheBustError::ReferenceCycle]. References of a file to itself are left as they are.
+	/// 
+	/// The hash of a rewritten CSS file covers its rewritten contents, while the `asset!` macro only sees the
+	/// original file. So `asset!` can't be used to reference CSS files containing references to other hashed
+	/// files, as it produces a name that doesn't exist in `out_dir`. Use the [Manifest] or the
+	/// [lookup module][CacheBustBuilder::lookup_module] to get their hashed names instead.
+	/// 
+	/// # Default
+	/// 
+	/// `false`
+	pub fn rewrite_css(mut self, rewrite_css: bool) -> Self {
+		self.rewrite_css = rewrite_css;
+		self
+	}
+	
+	/// Specifies whether [hash_dir][CacheBust::hash_dir] rewrites references to other files in JavaScript
+	/// files ending in `.js` or `.mjs` to point to their hashed names. The specifiers of static `import`
+	/// and `export ... from` declarations and of dynamic `import()` calls with a string literal are rewritten
+	/// if they start with `./`, `../` or `/`, while bare specifiers like `"lit"` refer to packages and are
+	/// left as they are. The URLs in `new URL(..., import.meta.url)` expressions are rewritten as well.
+	/// 
+	/// JavaScript files are ordered and hashed like CSS files with [rewrite_css][CacheBustBuilder::rewrite_css],
+	/// so modules importing each other in a cycle make [hash_dir][CacheBust::hash_dir] fail with
+	/// [CacheBustError::ReferenceCycle].
+	/// 
+	/// Like with [rewrite_css][CacheBustBuilder::rewrite_css], rewritten modules can't be referenced with the
+	/// `asset!` macro, as its hash only covers the original contents.
+	/// 
+	/// # Default
+	/// 
+	/// `false`
+	pub fn rewrite_js(mut self, rewrite_js: bool) -> Self {
+		self.rewrite_js = rewrite_js;
+		self
+	}
+	
+	/// Specifies what happens to source maps referenced by a `//# sourceMappingURL=` comment in a JavaScript
+	/// or CSS file when calling [hash_dir][CacheBust::hash_dir]. See [SourceMapPolicy] for the options.
+	/// 
+	/// With [SourceMapPolicy::Rewrite] each source map is hashed right after the file referencing it, so
+	/// its `file` field can point to the hashed name of that file. The hash of the file doesn't cover the
+	/// URL in its `sourceMappingURL` comment, as the URL contains the hash of the source map in turn.
+	/// 
+	/// Unless the policy is [SourceMapPolicy::Separate], files with a `sourceMappingURL` comment can't be
+	/// referenced with the `asset!` macro, as their hashes differ from the hashes of the original contents.
+	/// 
+	/// # Default
+	/// 
+	/// [SourceMapPolicy::Separate]
+	pub fn source_maps(mut self, policy: SourceMapPolicy) -> Self {
+		self.source_maps = policy;
+		self
+	}
+	
+	/// Adds a glob pattern for entry files, like `*.html`, which keep their names but have their
+	/// references to other files rewritten to the hashed names when calling [hash_dir][CacheBust::hash_dir].
+	/// Entry files are processed after all hashed files and copied to `out_dir`, or rewritten in-place
+	/// if `in_place` is set.
+	/// 
+	/// In HTML files the URLs in `src`, `href`, `srcset` and `poster` attributes and the `content` attribute
+	/// of `<meta>` tags are rewritten, as well as `url()` functions in `<style>` elements and `style` attributes.
+	/// CSS and JavaScript entry files are rewritten like with [rewrite_css][CacheBustBuilder::rewrite_css] and
+	/// [rewrite_js][CacheBustBuilder::rewrite_js], other entry files are copied as they are. Only URLs resolving
+	/// to hashed files in `in_dir` are rewritten.
+	/// 
+	/// Patterns are matched the same way as for [include][CacheBustBuilder::include], but files matching
+	/// an entry pattern don't have to match an `include` pattern. `exclude` patterns take precedence.
+	/// 
+	/// # Default
+	/// 
+	/// No files are entry files.
+	pub fn entry(mut self, pattern: impl Into<String>) -> Self {
+		self.entries.push(pattern.into());
+		self
+	}
+	
+	/// Adds a [Compression] to write a precompressed variant of each hashed file with when calling
+	/// [hash_dir][CacheBust::hash_dir] or [hash_file][CacheBust::hash_file], using its highest level.
+	/// The variants are written next to the hashed files with the [extension][Compression::extension] of
+	/// the algorithm appended, like `app.<hash>.js.br`, which lets web servers serve them directly.
+	/// 
+	/// Only files ending in one of the [precompress_extensions][CacheBustBuilder::precompress_extensions]
+	/// which are at least [precompress_min_size][CacheBustBuilder::precompress_min_size] bytes large are
+	/// compressed, and variants which aren't [precompress_min_savings][CacheBustBuilder::precompress_min_savings]
+	/// percent smaller than the file are skipped.
+	/// 
+	/// # Default
+	/// 
+	/// No precompressed variants are written.
+	pub fn precompress(self, compression: Compression) -> Self {
+		self.precompress_with_level(compression, compression.max_level())
+	}
+	
+	/// Adds a [Compression] like [precompress][CacheBustBuilder::precompress], but with the given level.
+	/// Adding the same algorithm again replaces its level.
+	/// 
+	/// # Default
+	/// 
+	/// No precompressed variants are written.
+	pub fn precompress_with_level(mut self, compression: Compression, level: u32) -> Self {
+		self.precompress.retain(|(existing, _)| *existing != compression);
+		self.precompress.push((compression, level));
+		self
+	}
+	
+	/// Specifies the extensions of the files to write precompressed variants of, without the leading `.`.
+	/// Extensions are compared case-insensitively.
+	/// 
+	/// # Default
+	/// 
+	/// [DEFAULT_PRECOMPRESS_EXTENSIONS]
+	pub fn precompress_extensions(mut self, extensions: impl IntoIterator<Item = impl Into<String>>) -> Self {
+		self.precompress_extensions = Some(extensions.into_iter().map(Into::into).collect());
+		self
+	}
+	
+	/// Specifies the minimum size in bytes of the files to write precompressed variants of.
+	/// 
+	/// # Default
+	/// 
+	/// [DEFAULT_PRECOMPRESS_MIN_SIZE]
+	pub fn precompress_min_size(mut self, min_size: u64) -> Self {
+		self.precompress_min_size = Some(min_size);
+		self
+	}
+	
+	/// Specifies by how many percent a precompressed variant has to be smaller than the original file to be
+	/// written. A value of `100` or more skips all variants.
+	/// 
+	/// # Default
+	/// 
+	/// [DEFAULT_PRECOMPRESS_MIN_SAVINGS]
+	pub fn precompress_min_savings(mut self, percent: u8) -> Self {
+		self.precompress_min_savings = Some(percent);
+		self
+	}
+	
 	/// Builds a [CacheBust] with the given options.
 	/// 
 	/// # Panics
@@ -162,17 +595,132 @@ impl CacheBustBuilder {
 			if out_dir.is_file() {
 				return Err(CacheBustBuilderError::OutDirIsAFile(out_dir.clone()));
 			}
+			
+			// paths that can't be canonicalized are reported when they're used
+			if let (Ok(in_dir), Some(out_dir)) = (in_dir.canonicalize(), canonicalize_partially(out_dir)) {
+				if out_dir.starts_with(&in_dir) {
+					return Err(CacheBustBuilderError::OutDirInsideInDir {in_dir, out_dir});
+				}
+				
+				if in_dir.starts_with(&out_dir) {
+					return Err(CacheBustBuilderError::InDirInsideOutDir {in_dir, out_dir});
+				}
+			}
+		}
+		
+		if self.is_build_script {
+			for var in ENV_VARS {
+				println!("cargo::rerun-if-env-changed={var}");
+			}
+		}
+		
+		let mut hash_options = HashOptions::from_env()
+			.map_err(CacheBustBuilderError::InvalidHashOptions)?;
+		
+		if let Some(algorithm) = self.hash_algorithm {
+			hash_options = hash_options.algorithm(algorithm);
+		}
+		
+		if let Some(encoding) = self.hash_encoding {
+			hash_options = hash_options.encoding(encoding);
+		}
+		
+		if let Some(length) = self.hash_length {
+			hash_options = hash_options.hash_length(length)
+				.map_err(CacheBustBuilderError::InvalidHashOptions)?;
+		}
+		
+		if let Some(template) = self.name_template {
+			hash_options = hash_options.name_template(template);
 		}
 		
+		if let Some(extensions) = self.compound_extensions {
+			hash_options = hash_options.compound_extensions(extensions);
+		}
+		
+		if let Some(integrity) = self.integrity {
+			hash_options = hash_options.integrity(integrity);
+		}
+		
+		let lookup_module = match self.lookup_module {
+			Some(path) if path.is_relative() => match std::env::var_os("OUT_DIR") {
+				Some(out_dir) => Some(PathBuf::from(out_dir).join(path)),
+				None => return Err(CacheBustBuilderError::LookupModuleOutDirNotSet(path)),
+			},
+			lookup_module => lookup_module,
+		};
+		
+		let filter = FileFilter::new(&self.include, &self.exclude, &self.entries)
+			.map_err(CacheBustBuilderError::InvalidGlob)?;
+		
+		if let Some(&(compression, level)) = self.precompress.iter().find(|(compression, level)| *level > compression.max_level()) {
+			return Err(CacheBustBuilderError::InvalidCompressionLevel {compression, level});
+		}
+		
+		let precompress_extensions = self.precompress_extensions
+			.map(|extensions| extensions.into_iter().map(|extension| extension.to_ascii_lowercase()).collect())
+			.unwrap_or_else(|| DEFAULT_PRECOMPRESS_EXTENSIONS.map(ToOwned::to_owned).to_vec());
+		
 		Ok(CacheBust {
 			in_dir,
 			out_dir,
 			is_build_script: self.is_build_script,
 			enable_logging: self.enable_logging,
+			hash_options,
+			manifest: self.manifest,
+			lookup_module,
+			import_map: self.import_map,
+			import_map_base: self.import_map_base.unwrap_or_else(|| "/".to_owned()),
+			filter,
+			incremental: self.incremental,
+			force: self.force,
+			rewrite_css: self.rewrite_css,
+			rewrite_js: self.rewrite_js,
+			source_maps: self.source_maps,
+			precompress: self.precompress,
+			precompress_extensions,
+			precompress_min_size: self.precompress_min_size.unwrap_or(DEFAULT_PRECOMPRESS_MIN_SIZE),
+			precompress_min_savings: self.precompress_min_savings.unwrap_or(DEFAULT_PRECOMPRESS_MIN_SAVINGS),
+			lookup_files: Mutex::default(),
 		})
 	}
 }
 
+/// Whether `out_dir` was created by cache_bust, i.e. it contains the [OUT_DIR_MARKER] file, or is empty.
+fn is_out_dir_owned(out_dir: &Path) -> bool {
+	!out_dir.is_dir()
+		|| out_dir.join(OUT_DIR_MARKER).is_file()
+		|| out_dir.read_dir().is_ok_and(|mut entries| entries.next().is_none())
+}
+
+/// Creates `out_dir` if necessary and writes the [OUT_DIR_MARKER] file into it if it is owned, so later
+/// runs can clear it. The marker is never written into a non-empty directory that doesn't have one yet.
+fn create_out_dir(out_dir: &Path) -> Result<(), CacheBustError> {
+	if !is_out_dir_owned(out_dir) {
+		return Ok(());
+	}
+	
+	fs::create_dir_all(out_dir)?;
+	fs::write(out_dir.join(OUT_DIR_MARKER), OUT_DIR_MARKER_CONTENTS)
+}
+
+/// Canonicalizes the longest existing prefix of `path` and appends the remaining components,
+/// which allows comparing paths that don't exist yet.
+fn canonicalize_partially(path: &Path) -> Option<PathBuf> {
+	let absolute = std::path::absolute(path).ok()?;
+	let mut existing = absolute.as_path();
+	let mut rest = Vec::new();
+	
+	loop {
+		if let Ok(canonical) = existing.canonicalize() {
+			return Some(rest.into_iter().rev().fold(canonical, |path, component| path.join(component)));
+		}
+		
+		rest.push(existing.file_name()?);
+		existing = existing.parent()?;
+	}
+}
+
 macro_rules! log {
 	($do_log: expr, $($msg: tt)*) => {
 		if $do_log {
@@ -188,6 +736,23 @@ pub struct CacheBust {
 	out_dir: Option<PathBuf>,
 	is_build_script: bool,
 	enable_logging: bool,
+	hash_options: HashOptions,
+	manifest: Option<PathBuf>,
+	lookup_module: Option<PathBuf>,
+	import_map: Option<PathBuf>,
+	import_map_base: String,
+	filter: FileFilter,
+	incremental: bool,
+	force: bool,
+	rewrite_css: bool,
+	rewrite_js: bool,
+	source_maps: SourceMapPolicy,
+	precompress: Vec<(Compression, u32)>,
+	precompress_extensions: Vec<String>,
+	precompress_min_size: u64,
+	precompress_min_savings: u8,
+	/// The files hashed so far, which are written to the lookup module
+	lookup_files: Mutex<Manifest>,
 }
 
 impl CacheBust {
@@ -200,9 +765,23 @@ impl CacheBust {
 	/// to include their hashes if `in_place` is set, or copies them to `out_dir` with
 	/// their hashes added to their names.
 	/// 
+	/// Returns a [HashDirReport] containing a [HashedAsset] for every hashed file. Files matching an
+	/// `exclude` pattern, or none of the `include` patterns, are either skipped or copied without being hashed.
+	/// 
+	/// If `in_place` is set, files whose names already contain the hash of their contents aren't
+	/// renamed again and are reported as [AssetStatus::Unchanged].  
+	/// If `incremental` is set `out_dir` isn't cleared. Instead, only files that don't exist yet are
+	/// copied and stale files are removed.  
+	/// A non-empty `out_dir` is only cleared or has stale files removed if it contains the [OUT_DIR_MARKER]
+	/// file, which is written by this function, or if `force` is set. Otherwise this returns
+	/// [CacheBustError::OutDirNotOwned].  
 	/// If `is_build_script` is set this emits the proper `cargo::rerun-if-changed` instruction.  
-	/// If `enable_logging` is set this will print out a message for every moved file.
-	pub fn hash_dir(&self) -> Result<(), io::Error> {
+	/// If `enable_logging` is set this will print out a message for every moved file.  
+	/// If `manifest` is set this writes a [Manifest] of all hashed files.  
+	/// If `lookup_module` is set this writes a Rust source file declaring an [AssetMap][crate::AssetMap] of all hashed files.  
+	/// If `import_map` is set this writes an [ImportMap][crate::ImportMap] of all hashed JavaScript modules.  
+	/// If `source_maps` isn't [SourceMapPolicy::Separate] source maps are rewritten or dropped along with the files referencing them.
+	pub fn hash_dir(&self) -> Result<HashDirReport, CacheBustError> {
 		if self.is_build_script {
 			println!("cargo::rerun-if-changed={}", self.in_dir.to_str()
 				.unwrap_or_else(|| panic!("could not register a build-time dependency on {:?}", self.in_dir))
@@ -210,37 +789,208 @@ impl CacheBust {
 		}
 		
 		if let Some(out_dir) = &self.out_dir {
-			if out_dir.is_dir() {
+			if !is_out_dir_owned(out_dir) && !self.force {
+				return Err(CacheBustError::OutDirNotOwned(out_dir.clone()));
+			}
+			
+			if out_dir.is_dir() && !self.incremental {
 				fs::remove_dir_all(out_dir)?;
 			}
+			
+			// with force set the directory is taken over, as it was just cleared or has stale files removed
+			fs::create_dir_all(out_dir)?;
+			fs::write(out_dir.join(OUT_DIR_MARKER), OUT_DIR_MARKER_CONTENTS)?;
 		}
 		
-		let in_dir_components = self.in_dir.components().count();
+		// collect the files first so the walk doesn't pick up files that were already
+		// moved, which can happen if the name template moves files into new directories
+		let mut files = Vec::new();
 		
 		for entry in WalkDir::new(&self.in_dir) {
-			let entry = entry?;
+			let entry = entry.map_err(walk_dir_error)?;
 			
-			if !entry.path().is_file() {
+			if entry.path().is_file() {
+				files.push(entry.into_path());
+			}
+		}
+		
+		let in_dir_components = self.in_dir.components().count();
+		let mut hasher = Hasher::new(self.hash_options.clone());
+		let mut assets = Vec::with_capacity(files.len());
+		let mut to_hash = Vec::with_capacity(files.len());
+		let mut entries = Vec::new();
+		let mut unhashed = Vec::new();
+		
+		for path in files {
+			let relative_path: PathBuf = path.components().skip(in_dir_components).collect();
+			
+			if self.out_dir.is_none() && self.is_precompressed_variant(&path) {
 				continue;
 			}
 			
-			let hashed_file_name = hashed_file_name(entry.path())?;
+			match self.filter.action(&relative_path) {
+				FileAction::Hash => {
+					if self.out_dir.is_none() {
+						if let Some(asset) = self.already_hashed(&mut hasher, &path, &relative_path)? {
+							log!(self.enable_logging, "[cache_bust/info] {path:?} is already hashed");
+							assets.push(asset);
+							continue;
+						}
+					}
+					
+					to_hash.push((path, relative_path));
+				},
+				FileAction::Exclude(ExcludePolicy::Skip) => {
+					log!(self.enable_logging, "[cache_bust/info] skipping {path:?}");
+				},
+				FileAction::Exclude(ExcludePolicy::CopyUnhashed) => {
+					unhashed.extend(self.write_unhashed(&path, &relative_path, None)?);
+				},
+				FileAction::Entry => entries.push((path, relative_path)),
+			}
+		}
+		
+		// source maps are hashed together with the file referencing them
+		let mut source_maps = self.pair_source_maps(&to_hash)?;
+		let paired: HashSet<PathBuf> = source_maps.values().map(|source_map| source_map.relative_path.clone()).collect();
+		to_hash.retain(|(_, relative_path)| !paired.contains(relative_path));
+		
+		// files whose references are rewritten are hashed last, as their contents depend
+		// on the hashed names of the files they reference
+		let mut documents = Vec::new();
+		
+		for (path, relative_path) in to_hash {
+			let source_map = source_maps.remove(&relative_path);
 			
-			if let Some(mut dest) = self.out_dir.clone() {
-				dest.extend(entry.path().components().skip(in_dir_components));
-				dest.pop();
-				fs::create_dir_all(&dest)?;
-				dest.push(hashed_file_name);
-				log!(self.enable_logging, "[cache_bust/info] copying {:?} -> {dest:?}", entry.path());
-				fs::copy(entry.path(), dest)?;
-			} else {
-				let new_path = entry.path().with_file_name(hashed_file_name);
-				log!(self.enable_logging, "[cache_bust/info] moving {:?} -> {new_path:?}", entry.path());
-				fs::rename(entry.path(), new_path)?;
+			match self.read_document(&path, &relative_path, source_map)? {
+				Some(document) => documents.push(document),
+				None => assets.push(self.hash_and_move(&mut hasher, path, relative_path)?),
 			}
 		}
 		
-		Ok(())
+		let mut asset_indices: HashMap<String, usize> = assets.iter()
+			.enumerate()
+			.map(|(index, asset)| (to_url_path(&asset.relative_path), index))
+			.collect();
+		
+		for document in self.order_documents(documents)? {
+			for asset in self.rewrite_and_hash(&mut hasher, document, &assets, &asset_indices)? {
+				asset_indices.insert(to_url_path(&asset.relative_path), assets.len());
+				assets.push(asset);
+			}
+		}
+		
+		for (path, relative_path) in entries {
+			let rewritten = self.read_entry(&path, &relative_path)?
+				.map(|document| self.rewrite_references(&document, 0, &assets, &asset_indices));
+			
+			unhashed.extend(self.write_unhashed(&path, &relative_path, rewritten)?);
+		}
+		
+		for asset in &mut assets {
+			asset.compressed = self.precompress(asset)?;
+		}
+		
+		let removed = match &self.out_dir {
+			Some(out_dir) if self.incremental => {
+				let marker = out_dir.join(OUT_DIR_MARKER);
+				let keep = assets.iter()
+					.flat_map(|asset| [asset.dest.as_path()].into_iter().chain(asset.compressed.iter().map(|compressed| compressed.dest.as_path())))
+					.chain(unhashed.iter().map(PathBuf::as_path))
+					.chain(self.manifest.as_deref())
+					.chain(self.lookup_module.as_deref())
+					.chain(self.import_map.as_deref())
+					.chain([marker.as_path()])
+					.collect();
+				
+				self.remove_stale_files(out_dir, &keep)?
+			},
+			_ => Vec::new(),
+		};
+		
+		if self.manifest.is_some() || self.lookup_module.is_some() || self.import_map.is_some() {
+			let manifest = Manifest::from_assets(&assets);
+			
+			if let Some(manifest_path) = &self.manifest {
+				log!(self.enable_logging, "[cache_bust/info] writing manifest to {manifest_path:?}");
+				manifest.write(manifest_path)?;
+			}
+			
+			self.write_lookup_module(&manifest)?;
+			
+			if let Some(import_map) = &self.import_map {
+				log!(self.enable_logging, "[cache_bust/info] writing import map to {import_map:?}");
+				manifest.to_import_map(&self.import_map_base).write(import_map)?;
+			}
+		}
+		
+		Ok(HashDirReport {
+			assets,
+			removed,
+		})
+	}
+	
+	/// Returns a [HashedAsset] for the file at `path` if its name already contains the hash of its contents.
+	fn already_hashed(&self, hasher: &mut Hasher, path: &Path, relative_path: &Path) -> Result<Option<HashedAsset>, CacheBustError> {
+		let Some(parsed) = parse_hashed_name_with(relative_path, &self.hash_options) else {
+			return Ok(None);
+		};
+		
+		let ContentHash {mut hash, integrity} = hasher.hash_file_with_integrity(path)?;
+		
+		// the hash of a file paired with its source map doesn't cover the URL of its sourceMappingURL comment,
+		// while its integrity string covers the whole file
+		if hash != parsed.hash && self.source_maps == SourceMapPolicy::Rewrite && has_source_map_comment(relative_path.extension().unwrap_or_default()) {
+			let contents = fs::read(path)?;
+			
+			if let Some(url) = std::str::from_utf8(&contents).ok().and_then(find_source_mapping_url) {
+				hash = self.hash_without(hasher, path, &contents, Some(url))?.hash;
+			}
+		}
+		
+		if hash != parsed.hash {
+			return Ok(None);
+		}
+		
+		Ok(Some(HashedAsset {
+			source: path.to_owned(),
+			dest: path.to_owned(),
+			hashed_relative_path: relative_path.to_owned(),
+			hashed_name: hasher.name_with_hash(&parsed.original, hash.clone()).name,
+			relative_path: parsed.original,
+			hash,
+			algorithm: self.hash_options.get_algorithm(),
+			integrity,
+			len: fs::file_len(path)?,
+			status: AssetStatus::Unchanged,
+			compressed: Vec::new(),
+		}))
+	}
+	
+	/// Removes all files in `out_dir` that aren't in `keep`, as well as directories left empty.
+	fn remove_stale_files(&self, out_dir: &Path, keep: &HashSet<&Path>) -> Result<Vec<PathBuf>, CacheBustError> {
+		let mut removed = Vec::new();
+		
+		if !out_dir.is_dir() {
+			return Ok(removed);
+		}
+		
+		for entry in WalkDir::new(out_dir).min_depth(1).contents_first(true) {
+			let entry = entry.map_err(walk_dir_error)?;
+			let path = entry.path();
+			
+			if entry.file_type().is_dir() {
+				if path.read_dir().is_ok_and(|mut entries| entries.next().is_none()) {
+					fs::remove_dir(path)?;
+				}
+			} else if !keep.contains(path) {
+				log!(self.enable_logging, "[cache_bust/info] removing stale file {path:?}");
+				fs::remove_file(path)?;
+				removed.push(entry.into_path());
+			}
+		}
+		
+		Ok(removed)
 	}
 	
 	/// Hashes the specified file and either renames it to include its hash if `in_place` is set,
@@ -248,7 +998,11 @@ impl CacheBust {
 	/// 
 	/// If `file` is a relative path then it is relative to `in_dir` and the structure of subdirectories
 	/// containing the file is kept when copying it to `out_dir`.
-	pub fn hash_file(&self, file: impl AsRef<Path>) -> Result<PathBuf, io::Error> {
+	/// 
+	/// Returns a [HashedAsset] describing the hashed file. The `include` and `exclude`
+	/// patterns don't apply to files hashed this way.  
+	/// If `lookup_module` is set the file is added to it, along with the files hashed before.
+	pub fn hash_file(&self, file: impl AsRef<Path>) -> Result<HashedAsset, CacheBustError> {
 		let file = file.as_ref();
 		
 		let path = self.in_dir.join(file);
@@ -259,26 +1013,488 @@ impl CacheBust {
 			);
 		}
 		
-		let hashed_file_name = hashed_file_name(&path)?;
+		let relative_path = if file.is_relative() {
+			file.to_owned()
+		} else {
+			file.file_name().unwrap_or_default().into()
+		};
+		
+		if let Some(out_dir) = &self.out_dir {
+			create_out_dir(out_dir)?;
+		}
+		
+		let mut asset = self.hash_and_move(&mut Hasher::new(self.hash_options.clone()), path, relative_path)?;
+		asset.compressed = self.precompress(&asset)?;
+		
+		if self.lookup_module.is_some() {
+			self.write_lookup_module(&Manifest::from_assets([&asset]))?;
+		}
+		
+		Ok(asset)
+	}
+	
+	/// Adds the files of `manifest` to the files hashed so far and writes all of them to the lookup module, if set.
+	fn write_lookup_module(&self, manifest: &Manifest) -> Result<(), CacheBustError> {
+		let Some(lookup_module) = &self.lookup_module else {
+			return Ok(());
+		};
+		
+		let mut lookup_files = self.lookup_files.lock().unwrap_or_else(PoisonError::into_inner);
+		lookup_files.files.extend(manifest.files.iter().map(|(original, entry)| (original.clone(), entry.clone())));
+		
+		log!(self.enable_logging, "[cache_bust/info] writing lookup module to {lookup_module:?}");
+		lookup_files.write_lookup_module(lookup_module)
+	}
+	
+	/// Writes the precompressed variants of `asset` next to its hashed file. Variants which already exist are
+	/// kept if the hashed file is unchanged, as their names contain the hash of its contents.
+	fn precompress(&self, asset: &HashedAsset) -> Result<Vec<CompressedAsset>, CacheBustError> {
+		let mut compressed = Vec::new();
+		
+		let extension = asset.relative_path.extension().unwrap_or_default().to_string_lossy().to_ascii_lowercase();
+		
+		if self.precompress.is_empty() || asset.len < self.precompress_min_size || !self.precompress_extensions.contains(&extension) {
+			return Ok(compressed);
+		}
+		
+		let mut contents = None;
 		
-		let dest = if let Some(mut dest) = self.out_dir.clone() {
-			if file.is_relative() {
-				dest.push(file);
-				dest.pop();
+		for &(compression, level) in &self.precompress {
+			let mut dest = asset.dest.clone().into_os_string();
+			dest.push(".");
+			dest.push(compression.extension());
+			let dest = PathBuf::from(dest);
+			
+			if asset.status == AssetStatus::Unchanged && dest.is_file() {
+				compressed.push(CompressedAsset {
+					compression,
+					len: fs::file_len(&dest)?,
+					dest,
+				});
+				continue;
+			}
+			
+			let contents = match &contents {
+				Some(contents) => contents,
+				None => contents.insert(fs::read(&asset.dest)?),
+			};
+			
+			let variant = compression.compress(contents, level)
+				.map_err(|err| CacheBustError::io(Operation::Compress, &asset.dest, err))?;
+			
+			let max_len = asset.len * u64::from(100u8.saturating_sub(self.precompress_min_savings)) / 100;
+			
+			if variant.len() as u64 > max_len {
+				log!(self.enable_logging, "[cache_bust/info] skipping {compression} for {:?} as it doesn't save enough", asset.dest);
+				
+				if dest.is_file() {
+					fs::remove_file(&dest)?;
+				}
+				
+				continue;
+			}
+			
+			log!(self.enable_logging, "[cache_bust/info] writing {dest:?}");
+			fs::write(&dest, &variant)?;
+			
+			compressed.push(CompressedAsset {
+				compression,
+				dest,
+				len: variant.len() as u64,
+			});
+		}
+		
+		Ok(compressed)
+	}
+	
+	/// Whether the file at `path` is a precompressed variant written next to a hashed file.
+	fn is_precompressed_variant(&self, path: &Path) -> bool {
+		let extension = path.extension().unwrap_or_default();
+		
+		self.precompress.iter().any(|(compression, _)| extension == compression.extension())
+			&& path.with_extension("").is_file()
+	}
+	
+	fn hash_and_move(&self, hasher: &mut Hasher, source: PathBuf, relative_path: PathBuf) -> Result<HashedAsset, CacheBustError> {
+		let hashed_name = hasher.hash_name(&source)?;
+		self.write_asset(hasher, source, relative_path, hashed_name, None)
+	}
+	
+	/// Returns the [Syntax] of the references to rewrite in the file at `relative_path`, if any.
+	fn syntax(&self, relative_path: &Path) -> Option<Syntax> {
+		let extension = relative_path.extension().unwrap_or_default();
+		
+		if self.rewrite_css && extension.eq_ignore_ascii_case("css") {
+			Some(Syntax::Css)
+		} else if self.rewrite_js && is_js_module(relative_path) {
+			Some(Syntax::Js)
+		} else {
+			None
+		}
+	}
+	
+	/// Reads the file at `source` and finds its references if they are rewritten or it references a source map.
+	fn read_document(&self, source: &Path, relative_path: &Path, source_map: Option<SourceMapFile>) -> Result<Option<Document>, CacheBustError> {
+		let syntax = self.syntax(relative_path);
+		
+		if syntax.is_none() && source_map.is_none() {
+			return Ok(None);
+		}
+		
+		self.read_document_with(source, relative_path, syntax, source_map)
+	}
+	
+	fn read_document_with(
+		&self,
+		source: &Path,
+		relative_path: &Path,
+		syntax: Option<Syntax>,
+		source_map: Option<SourceMapFile>,
+	) -> Result<Option<Document>, CacheBustError> {
+		let Ok(contents) = String::from_utf8(fs::read(source)?) else {
+			println!("{}{source:?} isn't valid UTF-8, hashing it without rewriting references", warn_prefix(self.is_build_script));
+			return Ok(None);
+		};
+		
+		Ok(Some(Document {
+			source: source.to_owned(),
+			relative_path: relative_path.to_owned(),
+			references: syntax.map(|syntax| syntax.find_references(&contents)).unwrap_or_default(),
+			contents,
+			source_map,
+		}))
+	}
+	
+	/// Reads an entry file and finds its references if it is an HTML or CSS file.
+	fn read_entry(&self, source: &Path, relative_path: &Path) -> Result<Option<Document>, CacheBustError> {
+		let extension = relative_path.extension().unwrap_or_default();
+		
+		let syntax = if extension.eq_ignore_ascii_case("html") || extension.eq_ignore_ascii_case("htm") {
+			Syntax::Html
+		} else if extension.eq_ignore_ascii_case("css") {
+			Syntax::Css
+		} else if is_js_module(relative_path) {
+			Syntax::Js
+		} else {
+			return Ok(None);
+		};
+		
+		self.read_document_with(source, relative_path, Some(syntax), None)
+	}
+	
+	/// Finds the source maps referenced by the `sourceMappingURL` comments of the files in `to_hash`,
+	/// keyed by the relative path of the file referencing them. Each source map is paired with one file.
+	fn pair_source_maps(&self, to_hash: &[(PathBuf, PathBuf)]) -> Result<HashMap<PathBuf, SourceMapFile>, CacheBustError> {
+		let mut pairs = HashMap::new();
+		
+		if self.source_maps == SourceMapPolicy::Separate {
+			return Ok(pairs);
+		}
+		
+		let files: HashMap<String, &(PathBuf, PathBuf)> = to_hash.iter()
+			.map(|file| (to_url_path(&file.1), file))
+			.collect();
+		let mut paired = HashSet::new();
+		
+		for (path, relative_path) in to_hash {
+			if !has_source_map_comment(relative_path.extension().unwrap_or_default()) {
+				continue;
+			}
+			
+			let Ok(contents) = String::from_utf8(fs::read(path)?) else {
+				continue;
+			};
+			
+			let source_map = find_source_mapping_url(&contents)
+				.and_then(|url| resolve_url(&contents[url], relative_path))
+				.and_then(|resolved| files.get(&resolved.path));
+			
+			if let Some((map_source, map_relative_path)) = source_map {
+				if map_relative_path != relative_path && paired.insert(map_relative_path) {
+					pairs.insert(relative_path.clone(), SourceMapFile {
+						source: map_source.clone(),
+						relative_path: map_relative_path.clone(),
+					});
+				}
+			}
+		}
+		
+		Ok(pairs)
+	}
+	
+	/// Orders the documents so each one comes after the documents it references.
+	/// References of a document to itself are ignored.
+	fn order_documents(&self, documents: Vec<Document>) -> Result<Vec<Document>, CacheBustError> {
+		let indices: HashMap<String, usize> = documents.iter()
+			.enumerate()
+			.map(|(index, document)| (to_url_path(&document.relative_path), index))
+			.collect();
+		
+		let dependencies: Vec<Vec<usize>> = documents.iter()
+			.enumerate()
+			.map(|(index, document)| {
+				let mut dependencies: Vec<usize> = document.references.iter()
+					.filter_map(|reference| resolve_url(&document.contents[reference.range.clone()], &document.relative_path))
+					.filter_map(|resolved| indices.get(&resolved.path).copied())
+					.filter(|dependency| *dependency != index)
+					.collect();
+				
+				dependencies.sort_unstable();
+				dependencies.dedup();
+				dependencies
+			})
+			.collect();
+		
+		let order = graph::topological_order(&dependencies).map_err(|cycle| {
+			CacheBustError::ReferenceCycle(cycle.into_iter().map(|index| documents[index].source.clone()).collect())
+		})?;
+		
+		let mut documents: Vec<_> = documents.into_iter().map(Some).collect();
+		
+		Ok(order.into_iter()
+			.map(|index| documents[index].take().expect("each document should occur once"))
+			.collect())
+	}
+	
+	/// Rewrites the references in `document` to the hashed names of `assets` and hashes the result.
+	/// 
+	/// Returns the hashed document, preceded by its source map if it was rewritten as well.
+	fn rewrite_and_hash(
+		&self,
+		hasher: &mut Hasher,
+		document: Document,
+		assets: &[HashedAsset],
+		asset_indices: &HashMap<String, usize>,
+	) -> Result<Vec<HashedAsset>, CacheBustError> {
+		let depth = self.hash_options.get_name_template().depth();
+		let mut rewritten = self.rewrite_references(&document, depth, assets, asset_indices);
+		let Document {source, relative_path, source_map, ..} = document;
+		
+		let source_map = source_map.and_then(|source_map| Some((source_map, find_source_mapping_url(&rewritten)?)));
+		let mut hashed_source_map = None;
+		
+		let content_hash = match source_map {
+			Some((source_map, url)) if self.source_maps == SourceMapPolicy::Drop => {
+				rewritten = remove_source_mapping_comment(&rewritten, url);
+				
+				if self.out_dir.is_some() {
+					log!(self.enable_logging, "[cache_bust/info] skipping source map {:?}", source_map.source);
+				} else {
+					log!(self.enable_logging, "[cache_bust/info] removing source map {:?}", source_map.source);
+					fs::remove_file(&source_map.source)?;
+				}
+				
+				self.hash_without(hasher, &source, rewritten.as_bytes(), None)?
+			},
+			Some((source_map, url)) => {
+				let hash = self.hash_without(hasher, &source, rewritten.as_bytes(), Some(url.clone()))?.hash;
+				let hashed_name = hasher.name_with_hash(&source, hash.clone());
+				let url_path = to_url_path(&relative_path.with_file_name(&hashed_name.name));
+				
+				let source_map = self.hash_source_map(hasher, source_map, &url_path, depth, assets, asset_indices)?;
+				rewritten.replace_range(url, &relative_url(&relative_path, depth, &source_map.url_path()));
+				hashed_source_map = Some(source_map);
+				
+				// the integrity string has to cover the final contents, including the rewritten URL
+				let integrity = match self.hash_options.get_integrity() {
+					Some(_) => self.hash_without(hasher, &source, rewritten.as_bytes(), None)?.integrity,
+					None => None,
+				};
+				
+				ContentHash {hash, integrity}
+			},
+			None => self.hash_without(hasher, &source, rewritten.as_bytes(), None)?,
+		};
+		
+		let hashed_name = hasher.name_with_content_hash(&source, content_hash);
+		let asset = self.write_asset(hasher, source, relative_path, hashed_name, Some(rewritten.into_bytes()))?;
+		
+		Ok(hashed_source_map.into_iter().chain([asset]).collect())
+	}
+	
+	/// Hashes `contents` read from `source`, leaving out the bytes in the range `without`.
+	fn hash_without(&self, hasher: &mut Hasher, source: &Path, contents: &[u8], without: Option<Range<usize>>) -> Result<ContentHash, CacheBustError> {
+		let (before, after) = match without {
+			Some(range) => (&contents[..range.start], &contents[range.end..]),
+			None => (contents, &[][..]),
+		};
+		
+		hasher.hash_reader_with_integrity(before.chain(after))
+			.map_err(|err| CacheBustError::io(Operation::Hash, source, err))
+	}
+	
+	/// Rewrites the `file` field of a source map to `file_url_path`, the hashed URL path of the file
+	/// referencing it, as well as its `sources` and hashes the result.
+	fn hash_source_map(
+		&self,
+		hasher: &mut Hasher,
+		source_map: SourceMapFile,
+		file_url_path: &str,
+		depth: usize,
+		assets: &[HashedAsset],
+		asset_indices: &HashMap<String, usize>,
+	) -> Result<HashedAsset, CacheBustError> {
+		let SourceMapFile {source, relative_path} = source_map;
+		let contents = fs::read(&source)?;
+		let file = relative_url(&relative_path, depth, file_url_path);
+		
+		// sources that aren't assets, like the original source files, still have to be reached from the deeper directory
+		let rewritten = std::str::from_utf8(&contents).ok().and_then(|json| rewrite_source_map(json, file, |url| {
+			self.rewrite_url(url, &relative_path, depth, assets, asset_indices)
+				.or_else(|| (depth > 0 && is_path_relative(url)).then(|| format!("{}{}", "../".repeat(depth), url.trim())))
+		}));
+		
+		let contents = rewritten.map_or(contents, |rewritten| rewritten.into_bytes());
+		
+		let content_hash = self.hash_without(hasher, &source, &contents, None)?;
+		let hashed_name = hasher.name_with_content_hash(&source, content_hash);
+		self.write_asset(hasher, source, relative_path, hashed_name, Some(contents))
+	}
+	
+	/// Replaces the references in `document` with the hashed names of `assets`, for a document that will be
+	/// moved `depth` directories deeper than its original location.
+	fn rewrite_references(&self, document: &Document, depth: usize, assets: &[HashedAsset], asset_indices: &HashMap<String, usize>) -> String {
+		replace_references(&document.contents, &document.references, |url| {
+			self.rewrite_url(url, &document.relative_path, depth, assets, asset_indices)
+		})
+	}
+	
+	/// Returns the URL of the hashed asset `url` points to from the file at `relative_path`, or `None`
+	/// if it doesn't point to one of `assets`.
+	fn rewrite_url(&self, url: &str, relative_path: &Path, depth: usize, assets: &[HashedAsset], asset_indices: &HashMap<String, usize>) -> Option<String> {
+		let resolved = resolve_url(url, relative_path)?;
+		let asset = &assets[*asset_indices.get(&resolved.path)?];
+		
+		let new_url = if resolved.is_root_relative {
+			format!("/{}", asset.url_path())
+		} else {
+			let relative = relative_url(relative_path, depth, &asset.url_path());
+			
+			// keeps URLs like `./util.js` explicitly relative, which JavaScript imports require
+			if url.trim_start().starts_with("./") && !relative.starts_with("../") {
+				format!("./{relative}")
+			} else {
+				relative
+			}
+		};
+		
+		Some(append_suffix(new_url, resolved.suffix))
+	}
+	
+	/// Copies the file at `source` to `out_dir` without hashing it, or writes `contents` there instead if
+	/// given. If `in_place` is set, the file is only overwritten with `contents` if given.
+	/// 
+	/// Returns the path of the copied file in `out_dir`.
+	fn write_unhashed(&self, source: &Path, relative_path: &Path, contents: Option<String>) -> Result<Option<PathBuf>, CacheBustError> {
+		let Some(out_dir) = &self.out_dir else {
+			if let Some(contents) = contents {
+				if fs::read(source)? != contents.as_bytes() {
+					log!(self.enable_logging, "[cache_bust/info] rewriting {source:?}");
+					fs::write(source, contents)?;
+				}
+			}
+			
+			return Ok(None);
+		};
+		
+		let dest = out_dir.join(relative_path);
+		
+		let is_unchanged = self.incremental && dest.is_file() && match &contents {
+			Some(contents) => fs::read(&dest)? == contents.as_bytes(),
+			None => fs::read(&dest)? == fs::read(source)?,
+		};
+		
+		if is_unchanged {
+			log!(self.enable_logging, "[cache_bust/info] {dest:?} is unchanged");
+			return Ok(Some(dest));
+		}
+		
+		create_parent_dir(&dest)?;
+		
+		if let Some(contents) = contents {
+			log!(self.enable_logging, "[cache_bust/info] writing {source:?} -> {dest:?} without hashing");
+			fs::write(&dest, contents)?;
+		} else {
+			log!(self.enable_logging, "[cache_bust/info] copying {source:?} -> {dest:?} without hashing");
+			fs::copy(source, &dest)?;
+		}
+		
+		Ok(Some(dest))
+	}
+	
+	/// Copies or moves the file at `source` to its hashed name, or writes `contents` there instead if given.
+	fn write_asset(&self, hasher: &mut Hasher, source: PathBuf, relative_path: PathBuf, hashed_name: HashedName, contents: Option<Vec<u8>>) -> Result<HashedAsset, CacheBustError> {
+		let len = match &contents {
+			Some(contents) => contents.len() as u64,
+			None => fs::file_len(&source)?,
+		};
+		
+		let hashed_relative_path = relative_path.with_file_name(&hashed_name.path);
+		
+		let mut status = AssetStatus::Written;
+		
+		let dest = if let Some(out_dir) = &self.out_dir {
+			let dest = out_dir.join(&hashed_relative_path);
+			
+			// the name of the destination doesn't necessarily tie to the contents, e.g. with a
+			// truncated hash or a query-only template, so the contents are compared as well
+			let is_unchanged = self.incremental && dest.is_file() && fs::file_len(&dest)? == len && match &contents {
+				Some(contents) => fs::read(&dest)? == *contents,
+				None => hasher.hash_file(&dest)? == hashed_name.hash,
+			};
+			
+			if is_unchanged {
+				log!(self.enable_logging, "[cache_bust/info] {dest:?} is unchanged");
+				status = AssetStatus::Unchanged;
+			} else if let Some(contents) = &contents {
+				create_parent_dir(&dest)?;
+				log!(self.enable_logging, "[cache_bust/info] writing {source:?} -> {dest:?}");
+				fs::write(&dest, contents)?;
+			} else {
+				create_parent_dir(&dest)?;
+				log!(self.enable_logging, "[cache_bust/info] copying {source:?} -> {dest:?}");
+				fs::copy(&source, &dest)?;
 			}
 			
-			fs::create_dir_all(&dest)?;
-			dest.push(hashed_file_name);
-			log!(self.enable_logging, "[cache_bust/info] copying {path:?} -> {dest:?}");
-			fs::copy(path, &dest)?;
 			dest
 		} else {
-			let new_path = path.with_file_name(hashed_file_name);
-			log!(self.enable_logging, "[cache_bust/info] moving {path:?} -> {new_path:?}");
-			fs::rename(path, &new_path)?;
-			new_path
+			let dest = source.with_file_name(&hashed_name.path);
+			create_parent_dir(&dest)?;
+			
+			if let Some(contents) = &contents {
+				log!(self.enable_logging, "[cache_bust/info] writing {source:?} -> {dest:?}");
+				fs::write(&dest, contents)?;
+				
+				if dest != source {
+					fs::remove_file(&source)?;
+				}
+			} else {
+				log!(self.enable_logging, "[cache_bust/info] moving {source:?} -> {dest:?}");
+				fs::rename(&source, &dest)?;
+			}
+			
+			dest
 		};
 		
-		Ok(dest)
+		Ok(HashedAsset {
+			source,
+			dest,
+			relative_path,
+			hashed_relative_path,
+			hashed_name: hashed_name.name,
+			hash: hashed_name.hash,
+			algorithm: self.hash_options.get_algorithm(),
+			integrity: hashed_name.integrity,
+			len,
+			status,
+			compressed: Vec::new(),
+		})
+	}
+	
+	/// Parses a name produced by this [CacheBust] and returns the original name and the hash.
+	/// 
+	/// Returns `None` if `name` isn't a hashed name.
+	pub fn parse_hashed_name(&self, name: impl AsRef<Path>) -> Option<ParsedName> {
+		parse_hashed_name_with(name.as_ref(), &self.hash_options)
 	}
 }
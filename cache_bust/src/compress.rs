@@ -0,0 +1,133 @@
+//! Writing precompressed variants of hashed files
+
+use std::{error::Error, fmt::{self, Display}, io::{self, Write}, path::PathBuf, str::FromStr};
+
+/// The extensions of files that are precompressed by default, as they usually compress well.
+pub const DEFAULT_PRECOMPRESS_EXTENSIONS: [&str; 12] = [
+	"html",
+	"htm",
+	"css",
+	"js",
+	"mjs",
+	"json",
+	"map",
+	"svg",
+	"txt",
+	"xml",
+	"wasm",
+	"ico",
+];
+
+/// The minimum size in bytes of files that are precompressed by default.
+pub const DEFAULT_PRECOMPRESS_MIN_SIZE: u64 = 1024;
+
+/// The minimum percentage a precompressed variant has to be smaller than the original file by default.
+pub const DEFAULT_PRECOMPRESS_MIN_SAVINGS: u8 = 10;
+
+/// Compression algorithm used to write precompressed variants of hashed files
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub enum Compression {
+	/// [gzip](https://datatracker.ietf.org/doc/html/rfc1952), written with the extension `.gz`
+	Gzip,
+	/// [Brotli](https://datatracker.ietf.org/doc/html/rfc7932), written with the extension `.br`
+	Brotli,
+	/// [Zstandard](https://datatracker.ietf.org/doc/html/rfc8878), written with the extension `.zst`
+	Zstd,
+}
+
+impl Compression {
+	/// Returns the extension appended to the names of files compressed with this algorithm.
+	pub fn extension(self) -> &'static str {
+		match self {
+			Compression::Gzip => "gz",
+			Compression::Brotli => "br",
+			Compression::Zstd => "zst",
+		}
+	}
+	
+	/// Returns the name of the algorithm as used in the `Content-Encoding` HTTP header.
+	pub fn encoding(self) -> &'static str {
+		match self {
+			Compression::Gzip => "gzip",
+			Compression::Brotli => "br",
+			Compression::Zstd => "zstd",
+		}
+	}
+	
+	/// Returns the highest compression level, which is used by default.
+	pub fn max_level(self) -> u32 {
+		match self {
+			Compression::Gzip => 9,
+			Compression::Brotli => 11,
+			Compression::Zstd => 22,
+		}
+	}
+	
+	/// Compresses `data` with the given level, which has to be at most [max_level][Compression::max_level].
+	pub(crate) fn compress(self, data: &[u8], level: u32) -> io::Result<Vec<u8>> {
+		match self {
+			Compression::Gzip => {
+				let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::new(level));
+				encoder.write_all(data)?;
+				encoder.finish()
+			},
+			Compression::Brotli => {
+				let mut compressed = Vec::new();
+				let params = brotli::enc::BrotliEncoderParams {
+					quality: level as i32,
+					..Default::default()
+				};
+				brotli::BrotliCompress(&mut &data[..], &mut compressed, &params)?;
+				Ok(compressed)
+			},
+			Compression::Zstd => zstd::encode_all(data, level as i32),
+		}
+	}
+}
+
+impl Display for Compression {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Compression::Gzip => "gzip",
+			Compression::Brotli => "brotli",
+			Compression::Zstd => "zstd",
+		})
+	}
+}
+
+impl FromStr for Compression {
+	type Err = UnknownCompression;
+	
+	fn from_str(name: &str) -> Result<Self, Self::Err> {
+		match name.to_ascii_lowercase().as_str() {
+			"gzip" | "gz" => Ok(Compression::Gzip),
+			"brotli" | "br" => Ok(Compression::Brotli),
+			"zstd" | "zst" => Ok(Compression::Zstd),
+			_ => Err(UnknownCompression(name.to_owned())),
+		}
+	}
+}
+
+/// Error from parsing an unknown [Compression]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UnknownCompression(pub String);
+
+impl Display for UnknownCompression {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "unknown compression {:?}, expected gzip, brotli or zstd", self.0)
+	}
+}
+
+impl Error for UnknownCompression {}
+
+/// A precompressed variant of a [HashedAsset][crate::HashedAsset], written next to the hashed file
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CompressedAsset {
+	/// The algorithm the file was compressed with
+	pub compression: Compression,
+	/// The path of the compressed file, which is the path of the hashed file with the
+	/// [extension][Compression::extension] of the algorithm appended
+	pub dest: PathBuf,
+	/// The size of the compressed file in bytes
+	pub len: u64,
+}
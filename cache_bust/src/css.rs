@@ -0,0 +1,90 @@
+//! Finding references to other files in CSS
+
+use crate::reference::Reference;
+
+/// Finds the URLs in `url()` functions and `@import` rules, skipping comments and other strings.
+pub(crate) fn find_references(css: &str) -> Vec<Reference> {
+	let bytes = css.as_bytes();
+	let mut references = Vec::new();
+	let mut i = 0;
+	
+	while i < bytes.len() {
+		match bytes[i] {
+			b'/' if bytes.get(i + 1) == Some(&b'*') => {
+				i = css[i + 2..].find("*/").map_or(bytes.len(), |end| i + 2 + end + 2);
+			},
+			quote @ (b'"' | b'\'') => {
+				i = string_end(bytes, i + 1, quote).1;
+			},
+			b'\\' => i += 2,
+			b'u' | b'U' if starts_with_ignore_case(&bytes[i..], b"url(") && !is_ident_byte(bytes, i.wrapping_sub(1)) => {
+				let start = skip_whitespace(bytes, i + 4);
+				
+				match bytes.get(start) {
+					Some(&quote @ (b'"' | b'\'')) => {
+						let (end, next) = string_end(bytes, start + 1, quote);
+						references.push(Reference {
+							range: start + 1..end,
+						});
+						i = next;
+					},
+					_ => {
+						let end = css[start..].find(')').map_or(bytes.len(), |end| start + end);
+						let url = css[start..end].trim_end();
+						references.push(Reference {
+							range: start..start + url.len(),
+						});
+						i = end;
+					},
+				}
+			},
+			b'@' if starts_with_ignore_case(&bytes[i + 1..], b"import") => {
+				let start = skip_whitespace(bytes, i + 7);
+				
+				if let Some(&quote @ (b'"' | b'\'')) = bytes.get(start) {
+					let (end, next) = string_end(bytes, start + 1, quote);
+					references.push(Reference {
+						range: start + 1..end,
+					});
+					i = next;
+				} else {
+					i = start;
+				}
+			},
+			_ => i += 1,
+		}
+	}
+	
+	references.retain(|reference| !reference.range.is_empty());
+	references
+}
+
+/// Returns the end of the contents of a string starting at `i` and the index after its closing quote.
+fn string_end(bytes: &[u8], mut i: usize, quote: u8) -> (usize, usize) {
+	while i < bytes.len() {
+		match bytes[i] {
+			b'\\' => i += 2,
+			byte if byte == quote => return (i, i + 1),
+			b'\n' => return (i, i),
+			_ => i += 1,
+		}
+	}
+	
+	(bytes.len(), bytes.len())
+}
+
+fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+	while bytes.get(i).is_some_and(u8::is_ascii_whitespace) {
+		i += 1;
+	}
+	
+	i
+}
+
+fn starts_with_ignore_case(bytes: &[u8], prefix: &[u8]) -> bool {
+	bytes.get(..prefix.len()).is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+}
+
+fn is_ident_byte(bytes: &[u8], i: usize) -> bool {
+	bytes.get(i).is_some_and(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_'))
+}
@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+
+/// What happens to files matching an `exclude` pattern
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum ExcludePolicy {
+	/// The file is neither hashed nor copied to `out_dir`.
+	#[default]
+	Skip,
+	/// The file is copied to `out_dir` under its original name. Files are left as they are
+	/// if `in_place` is set.
+	CopyUnhashed,
+}
+
+/// What to do with a file found in `in_dir`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum FileAction {
+	Hash,
+	Exclude(ExcludePolicy),
+	Entry,
+}
+
+/// Decides which files are hashed based on the `include`, `exclude` and `entry` patterns
+#[derive(Clone, Debug)]
+pub(crate) struct FileFilter {
+	include: Option<GlobSet>,
+	exclude: GlobSet,
+	policies: Vec<ExcludePolicy>,
+	entries: GlobSet,
+}
+
+impl FileFilter {
+	pub fn new(include: &[String], exclude: &[(String, ExcludePolicy)], entries: &[String]) -> Result<Self, globset::Error> {
+		let include = match include {
+			[] => None,
+			include => Some(glob_set(include.iter().map(String::as_str))?),
+		};
+		
+		Ok(Self {
+			include,
+			exclude: glob_set(exclude.iter().map(|(pattern, _)| pattern.as_str()))?,
+			policies: exclude.iter().map(|(_, policy)| *policy).collect(),
+			entries: glob_set(entries.iter().map(String::as_str))?,
+		})
+	}
+	
+	/// Returns what to do with the file at `relative_path`. If multiple `exclude` patterns
+	/// match, the one added first decides the policy. Entry files aren't affected by `include`.
+	pub fn action(&self, relative_path: &Path) -> FileAction {
+		if let Some(index) = self.exclude.matches(relative_path).first() {
+			return FileAction::Exclude(self.policies[*index]);
+		}
+		
+		if self.entries.is_match(relative_path) {
+			return FileAction::Entry;
+		}
+		
+		match &self.include {
+			Some(include) if !include.is_match(relative_path) => FileAction::Exclude(ExcludePolicy::Skip),
+			_ => FileAction::Hash,
+		}
+	}
+}
+
+fn glob_set<'a>(patterns: impl IntoIterator<Item = &'a str>) -> Result<GlobSet, globset::Error> {
+	let mut builder = GlobSetBuilder::new();
+	
+	for pattern in patterns {
+		builder.add(glob(pattern)?);
+	}
+	
+	builder.build()
+}
+
+fn glob(pattern: &str) -> Result<Glob, globset::Error> {
+	GlobBuilder::new(pattern)
+		.literal_separator(true)
+		.build()
+}
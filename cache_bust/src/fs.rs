@@ -0,0 +1,75 @@
+//! Wrappers around [std::fs] which add the affected paths to errors
+
+use std::{fs, path::Path};
+
+use cache_bust_core::{CacheBustError, Operation};
+
+pub fn create_dir_all(path: impl AsRef<Path>) -> Result<(), CacheBustError> {
+	let path = path.as_ref();
+	fs::create_dir_all(path)
+		.map_err(|err| CacheBustError::io(Operation::CreateDir, path, err))
+}
+
+pub fn create_parent_dir(path: &Path) -> Result<(), CacheBustError> {
+	match path.parent() {
+		Some(parent) => create_dir_all(parent),
+		None => Ok(()),
+	}
+}
+
+pub fn copy(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<(), CacheBustError> {
+	let (from, to) = (from.as_ref(), to.as_ref());
+	fs::copy(from, to)
+		.map(|_| ())
+		.map_err(|err| CacheBustError::io_with_dest(Operation::Copy, from, to, err))
+}
+
+pub fn rename(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<(), CacheBustError> {
+	let (from, to) = (from.as_ref(), to.as_ref());
+	fs::rename(from, to)
+		.map_err(|err| CacheBustError::io_with_dest(Operation::Rename, from, to, err))
+}
+
+pub fn read(path: impl AsRef<Path>) -> Result<Vec<u8>, CacheBustError> {
+	let path = path.as_ref();
+	fs::read(path)
+		.map_err(|err| CacheBustError::io(Operation::Read, path, err))
+}
+
+pub fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<(), CacheBustError> {
+	let path = path.as_ref();
+	fs::write(path, contents)
+		.map_err(|err| CacheBustError::io(Operation::Write, path, err))
+}
+
+pub fn remove_file(path: impl AsRef<Path>) -> Result<(), CacheBustError> {
+	let path = path.as_ref();
+	fs::remove_file(path)
+		.map_err(|err| CacheBustError::io(Operation::Remove, path, err))
+}
+
+pub fn remove_dir(path: impl AsRef<Path>) -> Result<(), CacheBustError> {
+	let path = path.as_ref();
+	fs::remove_dir(path)
+		.map_err(|err| CacheBustError::io(Operation::RemoveDir, path, err))
+}
+
+pub fn remove_dir_all(path: impl AsRef<Path>) -> Result<(), CacheBustError> {
+	let path = path.as_ref();
+	fs::remove_dir_all(path)
+		.map_err(|err| CacheBustError::io(Operation::RemoveDir, path, err))
+}
+
+pub fn file_len(path: impl AsRef<Path>) -> Result<u64, CacheBustError> {
+	let path = path.as_ref();
+	fs::metadata(path)
+		.map(|metadata| metadata.len())
+		.map_err(|err| CacheBustError::io(Operation::Read, path, err))
+}
+
+pub fn walk_dir_error(err: walkdir::Error) -> CacheBustError {
+	CacheBustError::WalkDir {
+		path: err.path().map(ToOwned::to_owned),
+		source: err.into(),
+	}
+}
@@ -0,0 +1,57 @@
+//! Ordering assets so that every asset is hashed after the assets it references
+
+/// Returns the indices of the nodes ordered so that each node comes after its dependencies,
+/// where `dependencies[i]` contains the indices of the nodes node `i` depends on.
+/// 
+/// Returns the nodes forming a cycle as an error if there is one, in the order they reference each other.
+pub(crate) fn topological_order(dependencies: &[Vec<usize>]) -> Result<Vec<usize>, Vec<usize>> {
+	#[derive(Clone, Copy, PartialEq, Eq)]
+	enum State {
+		Unvisited,
+		Visiting,
+		Done,
+	}
+	
+	let mut states = vec![State::Unvisited; dependencies.len()];
+	let mut order = Vec::with_capacity(dependencies.len());
+	
+	for root in 0..dependencies.len() {
+		if states[root] != State::Unvisited {
+			continue;
+		}
+		
+		// stack of nodes being visited and the index of the next dependency to visit
+		let mut stack = vec![(root, 0)];
+		states[root] = State::Visiting;
+		
+		while let Some((node, next)) = stack.last_mut() {
+			let node = *node;
+			
+			let Some(&dependency) = dependencies[node].get(*next) else {
+				states[node] = State::Done;
+				order.push(node);
+				stack.pop();
+				continue;
+			};
+			
+			*next += 1;
+			
+			match states[dependency] {
+				State::Unvisited => {
+					states[dependency] = State::Visiting;
+					stack.push((dependency, 0));
+				},
+				State::Visiting => {
+					let start = stack.iter()
+						.position(|(node, _)| *node == dependency)
+						.expect("nodes being visited should be on the stack");
+					
+					return Err(stack[start..].iter().map(|(node, _)| *node).collect());
+				},
+				State::Done => (),
+			}
+		}
+	}
+	
+	Ok(order)
+}
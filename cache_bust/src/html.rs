@@ -0,0 +1,155 @@
+//! Finding references to other files in HTML
+
+use std::ops::Range;
+
+use crate::{css, reference::Reference};
+
+/// Finds the URLs in `src`, `href`, `srcset` and `poster` attributes, the `content` attribute of
+/// `<meta>` tags as well as `url()` functions in `<style>` elements and `style` attributes.
+pub(crate) fn find_references(html: &str) -> Vec<Reference> {
+	let bytes = html.as_bytes();
+	let mut references = Vec::new();
+	let mut i = 0;
+	
+	while let Some(offset) = html[i..].find('<') {
+		i += offset;
+		
+		if html[i..].starts_with("<!--") {
+			i = html[i..].find("-->").map_or(bytes.len(), |end| i + end + 3);
+			continue;
+		}
+		
+		let name_start = i + 1;
+		let name_end = name_start + bytes[name_start..].iter()
+			.take_while(|byte| byte.is_ascii_alphanumeric() || **byte == b'-')
+			.count();
+		
+		if name_end == name_start {
+			i += 1;
+			continue;
+		}
+		
+		let name = html[name_start..name_end].to_ascii_lowercase();
+		let (attributes, end) = parse_attributes(html, name_end);
+		i = end;
+		
+		for (attribute, value) in attributes {
+			match attribute.as_str() {
+				"src" | "href" | "poster" => references.push(Reference {
+					range: value,
+				}),
+				"content" if name == "meta" => references.push(Reference {
+					range: value,
+				}),
+				"srcset" => references.extend(srcset_references(html, value)),
+				"style" => references.extend(css_references(html, value)),
+				_ => (),
+			}
+		}
+		
+		// the contents of these elements aren't HTML
+		if matches!(name.as_str(), "script" | "style") {
+			let closing_tag = format!("</{name}");
+			let contents_end = find_ignore_case(html, i, &closing_tag).unwrap_or(bytes.len());
+			
+			if name == "style" {
+				references.extend(css_references(html, i..contents_end));
+			}
+			
+			i = contents_end;
+		}
+	}
+	
+	references.retain(|reference| !reference.range.is_empty());
+	references
+}
+
+/// Parses the attributes of a tag starting at `i` and returns their lowercase names with the
+/// ranges of their values, as well as the index after the end of the tag.
+fn parse_attributes(html: &str, mut i: usize) -> (Vec<(String, Range<usize>)>, usize) {
+	let bytes = html.as_bytes();
+	let mut attributes = Vec::new();
+	
+	loop {
+		while bytes.get(i).is_some_and(|byte| byte.is_ascii_whitespace() || *byte == b'/') {
+			i += 1;
+		}
+		
+		match bytes.get(i) {
+			None => return (attributes, i),
+			Some(b'>') => return (attributes, i + 1),
+			_ => (),
+		}
+		
+		let name_start = i;
+		
+		while bytes.get(i).is_some_and(|byte| !byte.is_ascii_whitespace() && !matches!(byte, b'=' | b'>' | b'/')) {
+			i += 1;
+		}
+		
+		let name = html[name_start..i].to_ascii_lowercase();
+		
+		while bytes.get(i).is_some_and(u8::is_ascii_whitespace) {
+			i += 1;
+		}
+		
+		if bytes.get(i) != Some(&b'=') {
+			continue;
+		}
+		
+		i += 1;
+		
+		while bytes.get(i).is_some_and(u8::is_ascii_whitespace) {
+			i += 1;
+		}
+		
+		let value = match bytes.get(i) {
+			Some(&quote @ (b'"' | b'\'')) => {
+				let start = i + 1;
+				let end = html[start..].find(quote as char).map_or(bytes.len(), |end| start + end);
+				i = (end + 1).min(bytes.len());
+				start..end
+			},
+			_ => {
+				let start = i;
+				
+				while bytes.get(i).is_some_and(|byte| !byte.is_ascii_whitespace() && *byte != b'>') {
+					i += 1;
+				}
+				
+				start..i
+			},
+		};
+		
+		attributes.push((name, value));
+	}
+}
+
+/// Splits a `srcset` attribute into the URLs of its image candidates.
+fn srcset_references(html: &str, value: Range<usize>) -> impl Iterator<Item = Reference> + '_ {
+	let mut start = value.start;
+	
+	html[value.clone()].split(',').map(move |candidate| {
+		let url_start = start + (candidate.len() - candidate.trim_start().len());
+		let url_len = candidate.trim_start().split(|c: char| c.is_ascii_whitespace()).next().unwrap_or_default().len();
+		start += candidate.len() + 1;
+		
+		Reference {
+			range: url_start..url_start + url_len,
+		}
+	})
+}
+
+/// Finds the references in CSS embedded in HTML, with ranges relative to the whole HTML.
+fn css_references(html: &str, range: Range<usize>) -> impl Iterator<Item = Reference> {
+	css::find_references(&html[range.clone()]).into_iter().map(move |reference| Reference {
+		range: reference.range.start + range.start..reference.range.end + range.start,
+	})
+}
+
+fn find_ignore_case(haystack: &str, start: usize, needle: &str) -> Option<usize> {
+	haystack.as_bytes()[start..]
+		.windows(needle.len())
+		.position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+		.map(|position| start + position)
+}
@@ -0,0 +1,241 @@
+//! Finding references to other files in JavaScript modules
+
+use std::{ops::Range, path::Path};
+
+use crate::reference::Reference;
+
+/// Whether the file at `path` is a JavaScript module, i.e. its extension is `js` or `mjs` in any case.
+pub(crate) fn is_js_module(path: &Path) -> bool {
+	let extension = path.extension().unwrap_or_default();
+	extension.eq_ignore_ascii_case("js") || extension.eq_ignore_ascii_case("mjs")
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Token {
+	Ident(Range<usize>),
+	/// A string literal, with the range of its contents
+	Str(Range<usize>),
+	Punct(u8),
+	/// Numbers, template literals and regular expressions
+	Other,
+}
+
+/// Finds the specifiers of static `import` and `export ... from` declarations, dynamic `import()` calls
+/// with a string literal and the URLs in `new URL(..., import.meta.url)` expressions.
+/// 
+/// Only specifiers starting with `./`, `../` or `/` are returned for imports, as others refer to packages.
+pub(crate) fn find_references(js: &str) -> Vec<Reference> {
+	let tokens = tokenize(js);
+	let ident = |token: &Token, name: &str| matches!(token, Token::Ident(range) if &js[range.clone()] == name);
+	let is_relative = |range: &Range<usize>| ["./", "../", "/"].iter().any(|prefix| js[range.clone()].starts_with(prefix));
+	
+	let mut references = Vec::new();
+	let mut in_declaration = false;
+	
+	for (i, token) in tokens.iter().enumerate() {
+		let next = |offset: usize| tokens.get(i + offset).unwrap_or(&Token::Other);
+		let is_property = i > 0 && tokens[i - 1] == Token::Punct(b'.');
+		
+		if token == &Token::Punct(b';') {
+			in_declaration = false;
+		} else if (ident(token, "import") || ident(token, "export")) && !is_property {
+			match (next(1), next(2)) {
+				// import "./module.js"
+				(Token::Str(range), _) if ident(token, "import") => {
+					if is_relative(range) {
+						references.push(Reference {
+							range: range.clone(),
+						});
+					}
+				},
+				// import("./module.js")
+				(Token::Punct(b'('), Token::Str(range)) if ident(token, "import") => {
+					if is_relative(range) && matches!(next(3), Token::Punct(b')' | b',')) {
+						references.push(Reference {
+							range: range.clone(),
+						});
+					}
+				},
+				(Token::Punct(b'.' | b'('), _) => (),
+				_ => in_declaration = true,
+			}
+		} else if in_declaration && ident(token, "from") {
+			if let Token::Str(range) = next(1) {
+				if is_relative(range) {
+					references.push(Reference {
+						range: range.clone(),
+					});
+				}
+				
+				in_declaration = false;
+			}
+		} else if ident(token, "new") && ident(next(1), "URL") && next(2) == &Token::Punct(b'(') {
+			// new URL("./worker.js", import.meta.url)
+			if let Token::Str(range) = next(3) {
+				let is_import_meta_url = next(4) == &Token::Punct(b',')
+					&& ident(next(5), "import")
+					&& next(6) == &Token::Punct(b'.')
+					&& ident(next(7), "meta")
+					&& next(8) == &Token::Punct(b'.')
+					&& ident(next(9), "url");
+				
+				if is_import_meta_url {
+					references.push(Reference {
+						range: range.clone(),
+					});
+				}
+			}
+		}
+	}
+	
+	references.retain(|reference| !reference.range.is_empty());
+	references
+}
+
+fn tokenize(js: &str) -> Vec<Token> {
+	let bytes = js.as_bytes();
+	let mut tokens = Vec::new();
+	let mut i = 0;
+	
+	while i < bytes.len() {
+		let byte = bytes[i];
+		
+		match byte {
+			_ if byte.is_ascii_whitespace() => i += 1,
+			b'/' if bytes.get(i + 1) == Some(&b'/') => {
+				i = js[i..].find('\n').map_or(bytes.len(), |end| i + end);
+			},
+			b'/' if bytes.get(i + 1) == Some(&b'*') => {
+				i = js[i + 2..].find("*/").map_or(bytes.len(), |end| i + 2 + end + 2);
+			},
+			b'/' if starts_regex(tokens.last(), js) => {
+				i = regex_end(bytes, i + 1);
+				tokens.push(Token::Other);
+			},
+			b'"' | b'\'' => {
+				let (end, next) = string_end(bytes, i + 1, byte);
+				tokens.push(Token::Str(i + 1..end));
+				i = next;
+			},
+			b'`' => {
+				i = template_end(bytes, i + 1);
+				tokens.push(Token::Other);
+			},
+			_ if is_ident_start(byte) => {
+				let start = i;
+				
+				while bytes.get(i).is_some_and(|byte| is_ident_start(*byte) || byte.is_ascii_digit()) {
+					i += 1;
+				}
+				
+				tokens.push(Token::Ident(start..i));
+			},
+			_ if byte.is_ascii_digit() => {
+				while bytes.get(i).is_some_and(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'.' | b'_')) {
+					i += 1;
+				}
+				
+				tokens.push(Token::Other);
+			},
+			_ => {
+				tokens.push(Token::Punct(byte));
+				i += 1;
+			},
+		}
+	}
+	
+	tokens
+}
+
+/// Whether a `/` following `previous` starts a regular expression rather than being a division.
+fn starts_regex(previous: Option<&Token>, js: &str) -> bool {
+	match previous {
+		None => true,
+		Some(Token::Punct(punct)) => !matches!(punct, b')' | b']' | b'}'),
+		Some(Token::Ident(range)) => matches!(
+			&js[range.clone()],
+			"return" | "typeof" | "instanceof" | "in" | "of" | "new" | "delete" | "void" | "throw" | "case" | "do" | "else" | "yield" | "await"
+		),
+		Some(Token::Str(_) | Token::Other) => false,
+	}
+}
+
+fn is_ident_start(byte: u8) -> bool {
+	byte.is_ascii_alphabetic() || matches!(byte, b'_' | b'$') || !byte.is_ascii()
+}
+
+/// Returns the end of the contents of a string starting at `i` and the index after its closing quote.
+fn string_end(bytes: &[u8], mut i: usize, quote: u8) -> (usize, usize) {
+	while i < bytes.len() {
+		match bytes[i] {
+			b'\\' => i += 2,
+			byte if byte == quote => return (i, i + 1),
+			b'\n' => return (i, i),
+			_ => i += 1,
+		}
+	}
+	
+	(bytes.len(), bytes.len())
+}
+
+/// Returns the index after the closing backtick of a template literal, skipping nested expressions.
+fn template_end(bytes: &[u8], mut i: usize) -> usize {
+	while i < bytes.len() {
+		match bytes[i] {
+			b'\\' => i += 2,
+			b'`' => return i + 1,
+			b'$' if bytes.get(i + 1) == Some(&b'{') => {
+				let mut depth = 0;
+				
+				while i < bytes.len() {
+					match bytes[i] {
+						b'{' => depth += 1,
+						b'}' => {
+							depth -= 1;
+							
+							if depth == 0 {
+								break;
+							}
+						},
+						quote @ (b'"' | b'\'') => i = string_end(bytes, i + 1, quote).1 - 1,
+						b'`' => i = template_end(bytes, i + 1) - 1,
+						_ => (),
+					}
+					
+					i += 1;
+				}
+				
+				i += 1;
+			},
+			_ => i += 1,
+		}
+	}
+	
+	bytes.len()
+}
+
+/// Returns the index after the flags of a regular expression starting at `i`.
+fn regex_end(bytes: &[u8], mut i: usize) -> usize {
+	let mut in_class = false;
+	
+	while i < bytes.len() {
+		match bytes[i] {
+			b'\\' => i += 1,
+			b'[' => in_class = true,
+			b']' => in_class = false,
+			b'/' if !in_class => break,
+			b'\n' => return i,
+			_ => (),
+		}
+		
+		i += 1;
+	}
+	
+	i += 1;
+	
+	while bytes.get(i).is_some_and(u8::is_ascii_alphabetic) {
+		i += 1;
+	}
+	
+	i.min(bytes.len())
+}
@@ -34,6 +34,45 @@
 //! environment variable to `1`. In this case the macro will act as an identity function,
 //! while still erroring if the file doesn't exist.
 //! 
+//! The hash algorithm defaults to SHA-256 and can be changed by setting the
+//! `CACHE_BUST_HASH_ALGORITHM` environment variable to one of `sha256`, `sha384`,
+//! `sha512`, `blake3` or `xxh3`. This variable is also read at build time, so
+//! setting it once (e.g. in the `[env]` section of `.cargo/config.toml`) keeps the
+//! names produced by the macro and the hashed files on disk in sync.
+//! 
+//! Similarly `CACHE_BUST_HASH_ENCODING` selects how the hash is written (`hex`, `base32`
+//! or `base64url`) and `CACHE_BUST_HASH_LENGTH` truncates it to the given number of
+//! characters, which must be at least 8.
+//! 
+//! Where the hash is added is determined by `CACHE_BUST_NAME_TEMPLATE` using the
+//! placeholders `{stem}`, `{hash}` and `{ext}`. It defaults to `{stem}.{hash}.{ext}`,
+//! other layouts are e.g. `{stem}-{hash}.{ext}`, `{hash}/{stem}.{ext}` or `{stem}.{ext}?v={hash}`.
+//! Extensions consisting of multiple parts, like `min.js`, `js.map` or `tar.gz`, are kept together,
+//! so `app.min.js` becomes `app.<hash>.min.js`. The recognized extensions can be set as a
+//! comma-separated list in `CACHE_BUST_COMPOUND_EXTENSIONS`.
+//! 
+//! The [`asset_integrity!`][`asset_integrity`] macro expands to the [Subresource Integrity] string of a file,
+//! looked up the same way, for the `integrity` attribute of `<script>` and `<link>` elements:
+//! ```
+//! use cache_bust::{asset, asset_integrity};
+//! 
+//! let img = format!(r#"<img src="{}">"#, asset!("images/circle.png"));
+//! let integrity = asset_integrity!("images/circle.png");
+//! assert!(integrity.starts_with("sha384-"));
+//! ```
+//! 
+//! It uses SHA-384 unless `CACHE_BUST_INTEGRITY` is set to `sha256` or `sha512`.
+//! 
+//! To only get the hash of a file, e.g. for an ETag or a `?v=` query string, use the [`asset_hash!`][`asset_hash`]
+//! macro, which uses the same algorithm and encoding as [`asset!`][`asset`] and expands to an empty string if
+//! `CACHE_BUST_SKIP_HASHING` is set to `1`:
+//! ```
+//! use cache_bust::asset_hash;
+//! 
+//! let version = asset_hash!("images/circle.png");
+//! assert_eq!(version, "f04a632bf7de8a58d730988671a9139d6f7b3b197bbc78b6c74a4542eaa4878d");
+//! ```
+//! 
 //! ## Build time
 //! 
 //! The next step is to rename the files on disk to include their hashes.
@@ -46,7 +85,7 @@
 //! 
 //! The Rust API can be called from a `build.rs` build script:
 //! ```
-//! # fn main() -> Result<(), std::io::Error> {
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
 //! use cache_bust::CacheBust;
 //! 
 //! let cache_bust = CacheBust::builder()
@@ -62,6 +101,57 @@
 //! # }
 //! ```
 //! 
+//! To let other tools know which hashed names were produced, a JSON manifest mapping
+//! each original path to its hashed path, hash and size can be written by setting
+//! `.manifest("assets.json")` on the builder or passing `--manifest assets.json` to the CLI.
+//! 
+//! Files which have to keep their names, like `index.html` or `robots.txt`, can be copied without
+//! being hashed using `.exclude_with("index.html", ExcludePolicy::CopyUnhashed)`, while `.exclude("**/.DS_Store")`
+//! skips files entirely and `.include("**/*.js")` restricts hashing to matching files. The CLI has the
+//! equivalent options `--pass-through`, `--exclude` and `--include`.
+//! 
+//! By default `out_dir` is cleared before the files are copied. Setting `.incremental(true)` or passing
+//! `--incremental` instead only copies files whose hashed names don't exist yet and removes stale files.
+//! To avoid deleting unrelated files by mistake, a non-empty `out_dir` is only cleared if it contains
+//! the `.cache_bust` marker file written by cache_bust, unless `.force(true)` or `--force` is set.
+//! 
+//! Stylesheets referencing other assets, like `url(../images/circle.png)`, would point to files that no
+//! longer exist after hashing. Setting `.rewrite_css(true)` or passing `--rewrite-css` rewrites `url()` and
+//! `@import` references in CSS files to the hashed names, hashing each stylesheet after the files it references.
+//! Stylesheets importing each other are hashed in dependency order, so a changed image also changes the hash
+//! of every stylesheet referencing it, directly or indirectly. Files referencing each other in a cycle result in an error.
+//! 
+//! JavaScript modules are rewritten the same way with `.rewrite_js(true)` or `--rewrite-js`, covering relative
+//! specifiers of static imports, dynamic `import()` calls with a string literal and `new URL("...", import.meta.url)`.
+//! Bare specifiers like `"lit"` are left as they are.
+//! 
+//! Alternatively an import map mapping the URLs of JavaScript modules to their hashed URLs can be written with
+//! `.import_map("importmap.json")` or `--import-map importmap.json`, which leaves the modules unchanged. A path ending
+//! in `.html` writes a `<script type="importmap">` element instead, and `.import_map_base("/assets/")` sets the URL
+//! `out_dir` is served at.
+//! 
+//! Stylesheets and modules rewritten this way, as well as files whose source maps are rewritten or dropped as
+//! described below, are hashed with their new contents, while the `asset!` macro hashes the original file. Their
+//! names have to be looked up in the manifest or the lookup module instead, as `asset!` would produce a name that
+//! doesn't exist in `out_dir`.
+//! 
+//! Source maps are hashed like any other file by default, which leaves the `//# sourceMappingURL=bundle.js.map`
+//! comment of `bundle.js` pointing to a file that no longer exists. With `.source_maps(SourceMapPolicy::Rewrite)` or
+//! `--source-maps rewrite` the comment is rewritten to the hashed source map, whose `file` and `sources` fields are
+//! rewritten in turn, while `SourceMapPolicy::Drop` or `--source-maps drop` leaves the source maps out.
+//! 
+//! Web servers can serve precompressed files directly. Adding `.precompress(Compression::Brotli)` or passing
+//! `--precompress brotli` writes a variant like `app.<hash>.js.br` next to each hashed text file, which is recorded
+//! in the manifest. Gzip and zstd are supported as well, and variants which don't save enough are skipped.
+//! 
+//! For `<script integrity="...">` and `<link integrity="...">` attributes, `.integrity(IntegrityAlgorithm::Sha384)` or
+//! `--integrity sha384` computes a [Subresource Integrity] string of each file while hashing it, which is exposed as
+//! [HashedAsset::integrity] and written to the manifest. It can also be set with the `CACHE_BUST_INTEGRITY` environment variable.
+//! 
+//! Entry files like `index.html` have to keep their names, but reference hashed files. Adding them with
+//! `.entry("**/*.html")` or `--entry "**/*.html"` copies them unhashed after all other files, with the URLs in
+//! their `src`, `href`, `srcset` and `poster` attributes and `<meta content>` rewritten to the hashed names.
+//! 
 //! ### CLI-tool
 //! 
 //! Alternatively **cache_bust_cli** can be used from some other build tool
@@ -75,27 +165,58 @@
 //! Some files might be dynamically generated during build time and thus not
 //! possible to include using the [`asset!`][`asset`] macro. It's possible to
 //! individually hash these files and obtain their hashed names at build time.
-//! How to pass those names to the runtime is left to you.
+//! They can be passed to the runtime using a generated lookup module.
 //! 
 //! ### Rust API
 //! 
 //! ```
-//! # fn main() -> Result<(), std::io::Error> {
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
 //! use cache_bust::CacheBust;
 //! 
 //! let cache_bust = CacheBust::builder()
-//! 	.out_dir("hashed_assets".to_owned())
+//! 	.out_dir("hashed_generated".to_owned())
 //! 	.build();
 //! 
-//! let path = cache_bust.hash_file("generated/script.js")?;
+//! let asset = cache_bust.hash_file("generated/script.js")?;
 //! assert_eq!(
-//! 	std::fs::read_to_string(path)?,
+//! 	std::fs::read_to_string(asset.dest)?,
 //! 	"alert('Hello world');\n"
 //! );
 //! # Ok(())
 //! # }
 //! ```
 //! 
+//! To look up the hashed names at runtime, they can be written to a Rust source file declaring
+//! an [AssetMap], e.g. from a `build.rs` build script:
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use cache_bust::{CacheBust, Manifest};
+//! 
+//! let cache_bust = CacheBust::builder()
+//! 	.out_dir("hashed_generated".to_owned())
+//! 	.build();
+//! 
+//! let script = cache_bust.hash_file("generated/script.js")?;
+//! let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR")?);
+//! Manifest::from_assets([&script]).write_lookup_module(&out_dir.join("assets.rs"))?;
+//! # Ok(())
+//! # }
+//! ```
+//! 
+//! The generated file is then included in the crate:
+//! ```ignore
+//! mod assets {
+//! 	include!(concat!(env!("OUT_DIR"), "/assets.rs"));
+//! }
+//! 
+//! assert_eq!(assets::lookup("generated/script.js"), Some("generated/script.aab9[...].js"));
+//! ```
+//! 
+//! The same file is written by [`hash_dir`][CacheBust::hash_dir] and [`hash_file`][CacheBust::hash_file] when setting
+//! `.lookup_module("assets.rs")` on the builder, where relative paths are relative to `OUT_DIR`. It is rewritten after
+//! every call and contains all files hashed so far, which are looked up with `lookup(..)`,
+//! a shorthand for [`ASSETS.lookup(..)`][AssetMap::lookup].
+//! 
 //! ### CLI-tool
 //! 
 //! ```sh
@@ -111,19 +232,55 @@
 //! 
 //! ### macro
 //! 
-//! Enables the `asset!` procedural macro.
+//! Enables the `asset!`, `asset_hash!` and `asset_integrity!` procedural macros.
 //! 
 //! ### build
 //! 
 //! Enables the `CacheBust` and `CacheBustBuilder` structs for hashing files at build time.
 //! 
 //! [`Cache-Control-immutable`]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Cache-Control#caching_static_assets_with_cache_busting
+//! [Subresource Integrity]: https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity
 
 #[cfg(feature = "macro")]
 #[doc(inline)]
-pub use cache_bust_macro::asset;
+pub use cache_bust_macro::{asset, asset_hash, asset_integrity};
+
+mod lookup;
+pub use lookup::*;
 
 #[cfg(feature = "build")]
 mod cache_bust;
 #[cfg(feature = "build")]
+mod asset;
+#[cfg(feature = "build")]
+pub use asset::*;
+#[cfg(feature = "build")]
+mod compress;
+#[cfg(feature = "build")]
+pub use compress::*;
+#[cfg(feature = "build")]
+mod css;
+#[cfg(feature = "build")]
+mod filter;
+#[cfg(feature = "build")]
+pub use filter::ExcludePolicy;
+#[cfg(feature = "build")]
+mod fs;
+#[cfg(feature = "build")]
+mod graph;
+#[cfg(feature = "build")]
+mod html;
+#[cfg(feature = "build")]
+mod js;
+#[cfg(feature = "build")]
+mod manifest;
+#[cfg(feature = "build")]
+mod reference;
+#[cfg(feature = "build")]
+mod source_map;
+#[cfg(feature = "build")]
+pub use source_map::SourceMapPolicy;
+#[cfg(feature = "build")]
+pub use manifest::*;
+#[cfg(feature = "build")]
 pub use cache_bust::*;
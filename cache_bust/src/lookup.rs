@@ -0,0 +1,47 @@
+/// Map from the original paths of files to their hashed paths, which can be embedded into a binary.
+/// 
+/// An [AssetMap] is usually generated at build time by setting
+/// [lookup_module][crate::CacheBustBuilder::lookup_module] or calling
+/// [Manifest::write_lookup_module][crate::Manifest::write_lookup_module] and then included with
+/// `include!`, see [the crate documentation][crate#dynamic-files].
+#[derive(Clone, Copy, Debug)]
+pub struct AssetMap {
+	entries: &'static [(&'static str, &'static str)],
+}
+
+impl AssetMap {
+	/// Creates a map from pairs of original and hashed paths, which have to be
+	/// sorted by the original path.
+	pub const fn new(entries: &'static [(&'static str, &'static str)]) -> Self {
+		Self {
+			entries,
+		}
+	}
+	
+	/// Returns the hashed path of the file at `path`, which is relative to `in_dir` and uses `/`
+	/// as the separator. A leading `/` is ignored.
+	/// 
+	/// Returns `None` if the file wasn't hashed.
+	pub fn lookup(&self, path: &str) -> Option<&'static str> {
+		let path = path.strip_prefix('/').unwrap_or(path);
+		
+		self.entries.binary_search_by(|(original, _)| (*original).cmp(path))
+			.ok()
+			.map(|index| self.entries[index].1)
+	}
+	
+	/// Returns the number of files in the map.
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+	
+	/// Returns whether the map contains no files.
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+	
+	/// Returns an iterator over the pairs of original and hashed paths.
+	pub fn iter(&self) -> impl Iterator<Item = (&'static str, &'static str)> {
+		self.entries.iter().copied()
+	}
+}
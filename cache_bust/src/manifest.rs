@@ -0,0 +1,207 @@
+use std::{collections::BTreeMap, fmt::Write, path::Path};
+
+use cache_bust_core::{CacheBustError, HashAlgorithm};
+use serde::{Deserialize, Serialize};
+
+use crate::{fs, js::is_js_module, HashedAsset};
+
+/// Manifest mapping the original paths of files, relative to `in_dir`, to their hashed paths.
+/// 
+/// It is serialized as a JSON object with the original paths as keys:
+/// ```json
+/// {
+///   "images/circle.png": {
+///     "path": "images/circle.f04a[...].png",
+///     "hash": "f04a[...]",
+///     "size": 1234,
+///     "algorithm": "sha256"
+///   }
+/// }
+/// ```
+/// 
+/// If an [integrity algorithm][crate::CacheBustBuilder::integrity] is set, each entry additionally has an
+/// `integrity` field containing the Subresource Integrity string of the hashed file, like `"sha384-oqVu[...]"`.
+/// 
+/// Entries of files with precompressed variants additionally have a `compressed` field mapping
+/// the `Content-Encoding` of each variant, like `gzip` or `br`, to its path and size.
+#[derive(Clone, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Manifest {
+	/// The entries keyed by the original path of each file
+	pub files: BTreeMap<String, ManifestEntry>,
+}
+
+/// Entry of a [Manifest] describing a single hashed file
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ManifestEntry {
+	/// The hashed path relative to `out_dir`, including the query string if the
+	/// [NameTemplate][crate::NameTemplate] has one
+	pub path: String,
+	/// The encoded hash of the file's contents
+	pub hash: String,
+	/// The size of the file in bytes
+	pub size: u64,
+	/// The algorithm used to hash the file
+	#[serde(with = "algorithm")]
+	pub algorithm: HashAlgorithm,
+	/// The Subresource Integrity string of the hashed file, if an integrity algorithm is set
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub integrity: Option<String>,
+	/// The precompressed variants of the file keyed by their `Content-Encoding`
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub compressed: BTreeMap<String, CompressedEntry>,
+}
+
+/// Precompressed variant of a file in a [ManifestEntry]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct CompressedEntry {
+	/// The path of the compressed file relative to `out_dir`
+	pub path: String,
+	/// The size of the compressed file in bytes
+	pub size: u64,
+}
+
+impl Manifest {
+	/// Creates a manifest containing the given assets.
+	pub fn from_assets<'a>(assets: impl IntoIterator<Item = &'a HashedAsset>) -> Self {
+		let files = assets.into_iter()
+			.map(|asset| (to_url_path(&asset.relative_path), ManifestEntry {
+				path: asset.url_path(),
+				hash: asset.hash.clone(),
+				size: asset.len,
+				algorithm: asset.algorithm,
+				integrity: asset.integrity.clone(),
+				compressed: asset.compressed.iter()
+					.map(|compressed| (compressed.compression.encoding().to_owned(), CompressedEntry {
+						path: format!("{}.{}", to_url_path(&asset.hashed_relative_path), compressed.compression.extension()),
+						size: compressed.len,
+					}))
+					.collect(),
+			}))
+			.collect();
+		
+		Self {
+			files,
+		}
+	}
+	
+	/// Serializes the manifest as JSON.
+	pub fn to_json(&self) -> String {
+		serde_json::to_string_pretty(self).expect("manifest should be serializable")
+	}
+	
+	/// Writes the manifest as JSON to `path`, creating its parent directories if necessary.
+	pub fn write(&self, path: &Path) -> Result<(), CacheBustError> {
+		fs::create_parent_dir(path)?;
+		fs::write(path, self.to_json())
+	}
+	
+	/// Generates Rust source code declaring a `pub static ASSETS: cache_bust::AssetMap`
+	/// which maps the original paths to the hashed paths, and a
+	/// `pub fn lookup(path: &str) -> Option<&'static str>` shorthand for `ASSETS.lookup(path)`.
+	pub fn to_lookup_module(&self) -> String {
+		let mut module = String::from("// Generated by cache_bust, do not edit.\n\n");
+		module.push_str("pub static ASSETS: ::cache_bust::AssetMap = ::cache_bust::AssetMap::new(&[\n");
+		
+		for (original, entry) in &self.files {
+			writeln!(module, "\t({original:?}, {:?}),", entry.path).expect("writing to a String can't fail");
+		}
+		
+		module.push_str("]);\n\n");
+		module.push_str("/// Returns the hashed path of the asset at the original `path`, see [::cache_bust::AssetMap::lookup].\n");
+		module.push_str("pub fn lookup(path: &str) -> Option<&'static str> {\n\tASSETS.lookup(path)\n}\n");
+		module
+	}
+	
+	/// Writes the Rust source code generated by [to_lookup_module][Manifest::to_lookup_module]
+	/// to `path`, creating its parent directories if necessary.
+	pub fn write_lookup_module(&self, path: &Path) -> Result<(), CacheBustError> {
+		fs::create_parent_dir(path)?;
+		fs::write(path, self.to_lookup_module())
+	}
+	
+	/// Creates an [ImportMap] mapping the URL of each JavaScript module, i.e. each file ending in
+	/// `.js` or `.mjs` in any case, to its hashed URL. `base` is the URL `out_dir` is served at, like `/` or `/assets/`.
+	pub fn to_import_map(&self, base: &str) -> ImportMap {
+		let base = base.strip_suffix('/').unwrap_or(base);
+		
+		let imports = self.files.iter()
+			.filter(|(original, _)| is_js_module(Path::new(original)))
+			.map(|(original, entry)| (format!("{base}/{original}"), format!("{base}/{}", entry.path)))
+			.collect();
+		
+		ImportMap {
+			imports,
+		}
+	}
+}
+
+/// Browser [import map](https://developer.mozilla.org/en-US/docs/Web/HTML/Element/script/type/importmap)
+/// mapping the URLs of JavaScript modules to their hashed URLs, which lets modules import each other
+/// by their original names without rewriting them.
+/// 
+/// It is serialized as a JSON object:
+/// ```json
+/// {
+///   "imports": {
+///     "/scripts/app.min.js": "/scripts/app.min.e4f9[...].js"
+///   }
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub struct ImportMap {
+	/// The hashed URLs keyed by the original URL of each module
+	pub imports: BTreeMap<String, String>,
+}
+
+impl ImportMap {
+	/// Serializes the import map as JSON.
+	pub fn to_json(&self) -> String {
+		serde_json::to_string_pretty(self).expect("import map should be serializable")
+	}
+	
+	/// Serializes the import map as a `<script type="importmap">` element to be included in HTML.
+	pub fn to_script(&self) -> String {
+		// `</script>` inside the JSON would end the element early
+		let json = self.to_json().replace("</", "<\\/");
+		format!("<script type=\"importmap\">\n{json}\n</script>\n")
+	}
+	
+	/// Writes the import map to `path`, creating its parent directories if necessary. It is written as
+	/// a `<script type="importmap">` element if `path` ends in `.html` or `.htm`, otherwise as JSON.
+	pub fn write(&self, path: &Path) -> Result<(), CacheBustError> {
+		let extension = path.extension().unwrap_or_default();
+		
+		let contents = if extension.eq_ignore_ascii_case("html") || extension.eq_ignore_ascii_case("htm") {
+			self.to_script()
+		} else {
+			self.to_json()
+		};
+		
+		fs::create_parent_dir(path)?;
+		fs::write(path, contents)
+	}
+}
+
+/// Converts a relative path to a string using `/` as the separator.
+pub(crate) fn to_url_path(path: &Path) -> String {
+	path.components()
+		.map(|component| component.as_os_str().to_string_lossy())
+		.collect::<Vec<_>>()
+		.join("/")
+}
+
+mod algorithm {
+	use cache_bust_core::HashAlgorithm;
+	use serde::{de::Error, Deserialize, Deserializer, Serializer};
+	
+	pub fn serialize<S: Serializer>(algorithm: &HashAlgorithm, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(algorithm.name())
+	}
+	
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashAlgorithm, D::Error> {
+		String::deserialize(deserializer)?
+			.parse()
+			.map_err(D::Error::custom)
+	}
+}
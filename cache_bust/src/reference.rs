@@ -0,0 +1,178 @@
+//! Resolving and replacing references between assets
+
+use std::{ops::Range, path::{Component, Path, PathBuf}};
+
+use crate::{css, html, js, source_map::SourceMapFile};
+
+/// The languages in which references to other files are rewritten
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Syntax {
+	Css,
+	Html,
+	Js,
+}
+
+impl Syntax {
+	/// Finds the references to other files in `contents`, sorted by their position.
+	pub fn find_references(self, contents: &str) -> Vec<Reference> {
+		match self {
+			Syntax::Css => css::find_references(contents),
+			Syntax::Html => html::find_references(contents),
+			Syntax::Js => js::find_references(contents),
+		}
+	}
+}
+
+/// An asset whose references to other files are rewritten before it is hashed
+#[derive(Clone, Debug)]
+pub(crate) struct Document {
+	pub source: PathBuf,
+	pub relative_path: PathBuf,
+	pub contents: String,
+	pub references: Vec<Reference>,
+	/// The source map referenced by the `sourceMappingURL` comment, if it is rewritten or dropped
+	pub source_map: Option<SourceMapFile>,
+}
+
+/// A reference to another file found in the contents of an asset
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct Reference {
+	/// The byte range of the URL inside the contents
+	pub range: Range<usize>,
+}
+
+/// A [Reference] resolved to the path of a file relative to `in_dir`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct ResolvedUrl<'a> {
+	/// The referenced path relative to `in_dir`, using `/` as the separator
+	pub path: String,
+	/// Whether the URL starts with `/`, making it relative to the root of `in_dir`
+	pub is_root_relative: bool,
+	/// The query string and fragment of the URL, including the leading `?` or `#`
+	pub suffix: &'a str,
+}
+
+/// Resolves `url` found in the file at `relative_path`, which is relative to `in_dir`.
+/// 
+/// Returns `None` for URLs with a scheme, like `https:` or `data:`, protocol-relative URLs,
+/// fragment-only URLs and relative URLs pointing outside of `in_dir`.
+pub(crate) fn resolve_url<'a>(url: &'a str, relative_path: &Path) -> Option<ResolvedUrl<'a>> {
+	let url = url.trim();
+	
+	if url.is_empty() || url.starts_with('#') || url.starts_with("//") || has_scheme(url) {
+		return None;
+	}
+	
+	let (url, suffix) = url.split_at(url.find(['?', '#']).unwrap_or(url.len()));
+	let (url, is_root_relative) = match url.strip_prefix('/') {
+		Some(url) => (url, true),
+		None => (url, false),
+	};
+	
+	let mut segments: Vec<String> = Vec::new();
+	
+	if !is_root_relative {
+		for component in relative_path.parent().into_iter().flat_map(Path::components) {
+			if let Component::Normal(segment) = component {
+				segments.push(segment.to_str()?.to_owned());
+			}
+		}
+	}
+	
+	for segment in url.split('/') {
+		match segment {
+			"" | "." => (),
+			".." => {
+				segments.pop()?;
+			},
+			segment => segments.push(segment.to_owned()),
+		}
+	}
+	
+	Some(ResolvedUrl {
+		path: segments.join("/"),
+		is_root_relative,
+		suffix,
+	})
+}
+
+/// Whether `url` is a path-relative URL like `src/main.ts` or `../main.ts`, which has to be prefixed with
+/// `../` for every directory the file containing it is moved deeper.
+pub(crate) fn is_path_relative(url: &str) -> bool {
+	let url = url.trim();
+	!url.is_empty() && !url.starts_with(['#', '/']) && !has_scheme(url)
+}
+
+fn has_scheme(url: &str) -> bool {
+	let Some((scheme, _)) = url.split_once(':') else {
+		return false;
+	};
+	
+	scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+		&& scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Builds the URL referencing `target`, a hashed URL path relative to `out_dir`, from the file at
+/// `relative_path` once that file has been moved `depth` directories deeper by the
+/// [NameTemplate][crate::NameTemplate].
+pub(crate) fn relative_url(relative_path: &Path, depth: usize, target: &str) -> String {
+	let from: Vec<_> = relative_path.parent()
+		.into_iter()
+		.flat_map(Path::components)
+		.map(|component| component.as_os_str().to_string_lossy())
+		.collect();
+	
+	let (target_path, query) = target.split_at(target.find('?').unwrap_or(target.len()));
+	let target: Vec<_> = target_path.split('/').collect();
+	let (target_dirs, target_name) = target.split_at(target.len() - 1);
+	
+	let common = from.iter()
+		.zip(target_dirs)
+		.take_while(|(from, target)| from == *target)
+		.count();
+	
+	let mut url = "../".repeat(depth + from.len() - common);
+	
+	for segment in &target_dirs[common..] {
+		url.push_str(segment);
+		url.push('/');
+	}
+	
+	url.push_str(target_name[0]);
+	url.push_str(query);
+	url
+}
+
+/// Appends the query string and fragment of the original URL to a hashed URL, which might already
+/// have a query string of its own.
+pub(crate) fn append_suffix(mut url: String, suffix: &str) -> String {
+	match suffix.strip_prefix('?') {
+		Some(query) if url.contains('?') => {
+			url.push('&');
+			url.push_str(query);
+		},
+		_ => url.push_str(suffix),
+	}
+	
+	url
+}
+
+/// Replaces the URLs at the given references, which have to be sorted and not overlap. URLs for
+/// which `replace` returns `None` are kept as they are.
+pub(crate) fn replace_references(contents: &str, references: &[Reference], mut replace: impl FnMut(&str) -> Option<String>) -> String {
+	let mut replaced = String::with_capacity(contents.len());
+	let mut end = 0;
+	
+	for reference in references {
+		let url = &contents[reference.range.clone()];
+		
+		if let Some(new_url) = replace(url) {
+			replaced.push_str(&contents[end..reference.range.start]);
+			replaced.push_str(&new_url);
+			end = reference.range.end;
+		}
+	}
+	
+	replaced.push_str(&contents[end..]);
+	replaced
+}
@@ -0,0 +1,85 @@
+//! Pairing scripts and stylesheets with their source maps
+
+use std::{ffi::OsStr, ops::Range, path::PathBuf};
+
+use serde_json::Value;
+
+/// What happens to source maps referenced by the `sourceMappingURL` comment of a JavaScript or CSS file
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum SourceMapPolicy {
+	/// Source maps are hashed like any other file and `sourceMappingURL` comments are left as they are.
+	#[default]
+	Separate,
+	/// The `sourceMappingURL` comment is rewritten to the hashed name of the source map, whose `file` and
+	/// `sources` fields are in turn rewritten to the hashed names of the files they point to.
+	Rewrite,
+	/// Source maps aren't written to `out_dir`, or are removed if `in_place` is set, and the
+	/// `sourceMappingURL` comment is removed.
+	Drop,
+}
+
+/// A source map referenced by the `sourceMappingURL` comment of a file that is hashed
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct SourceMapFile {
+	pub source: PathBuf,
+	pub relative_path: PathBuf,
+}
+
+/// Whether files with this extension can reference a source map.
+pub(crate) fn has_source_map_comment(extension: &OsStr) -> bool {
+	["js", "mjs", "css"].iter().any(|script| extension.eq_ignore_ascii_case(script))
+}
+
+/// Finds the URL of the last `//# sourceMappingURL=` or `/*# sourceMappingURL= */` comment in `contents`.
+pub(crate) fn find_source_mapping_url(contents: &str) -> Option<Range<usize>> {
+	const PREFIX: &str = "sourceMappingURL=";
+	
+	contents.rmatch_indices(PREFIX).find_map(|(i, _)| {
+		let line_start = contents[..i].rfind('\n').map_or(0, |start| start + 1);
+		
+		if !matches!(contents[line_start..i].trim(), "//#" | "//@" | "/*#" | "/*@") {
+			return None;
+		}
+		
+		let start = i + PREFIX.len();
+		let len = contents[start..]
+			.find(|c: char| c.is_whitespace() || c == '*')
+			.unwrap_or(contents.len() - start);
+		
+		Some(start..start + len)
+	})
+}
+
+/// Removes the whole line containing the `sourceMappingURL` comment whose URL is at `url`.
+pub(crate) fn remove_source_mapping_comment(contents: &str, url: Range<usize>) -> String {
+	let line_start = contents[..url.start].rfind('\n').map_or(0, |start| start + 1);
+	let line_end = contents[url.end..].find('\n').map_or(contents.len(), |end| url.end + end + 1);
+	
+	let mut removed = String::with_capacity(contents.len());
+	removed.push_str(&contents[..line_start]);
+	removed.push_str(&contents[line_end..]);
+	removed
+}
+
+/// Sets the `file` field of a source map and rewrites the URLs in its `sources` field with `rewrite_source`,
+/// unless the map has a `sourceRoot`. Returns `None` if `json` isn't a source map.
+pub(crate) fn rewrite_source_map(json: &str, file: String, mut rewrite_source: impl FnMut(&str) -> Option<String>) -> Option<String> {
+	let mut map: Value = serde_json::from_str(json).ok()?;
+	let map_object = map.as_object_mut()?;
+	
+	map_object.insert("file".to_owned(), Value::String(file));
+	
+	let has_source_root = map_object.get("sourceRoot").and_then(Value::as_str).is_some_and(|root| !root.is_empty());
+	
+	if let (false, Some(Value::Array(sources))) = (has_source_root, map_object.get_mut("sources")) {
+		for source in sources {
+			if let Value::String(url) = source {
+				if let Some(new_url) = rewrite_source(url) {
+					*url = new_url;
+				}
+			}
+		}
+	}
+	
+	Some(map.to_string())
+}
@@ -0,0 +1,11 @@
+// Generated by cache_bust, do not edit.
+
+pub static ASSETS: ::cache_bust::AssetMap = ::cache_bust::AssetMap::new(&[
+	("generated/script.js", "generated/script.aab92fa2.js"),
+	("hello.txt", "hello.d9014c46.txt"),
+]);
+
+/// Returns the hashed path of the asset at the original `path`, see [::cache_bust::AssetMap::lookup].
+pub fn lookup(path: &str) -> Option<&'static str> {
+	ASSETS.lookup(path)
+}
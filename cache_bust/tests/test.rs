@@ -1,6 +1,7 @@
-use std::{env, fs::{self, File}, path::PathBuf};
+use std::{env, fs::{self, File}, path::{Path, PathBuf}};
 
-use cache_bust::CacheBust;
+use cache_bust::{AssetMap, AssetStatus, CacheBust, CacheBustBuilderError, CacheBustError, Compression, ExcludePolicy, HashAlgorithm, HashEncoding, ImportMap, IntegrityAlgorithm, Manifest, Operation, SourceMapPolicy, OUT_DIR_MARKER};
+use cache_bust_core::{HashOptions, Hasher};
 
 fn assets_dir() -> PathBuf {
 	let mut dir: PathBuf = env!("CARGO_MANIFEST_DIR").into();
@@ -25,12 +26,101 @@ fn out_dir_is_cleared() {
 	
 	CacheBust::builder()
 		.out_dir(temp_dir)
+		.force(true)
 		.build()
 		.hash_dir().unwrap();
 	
 	assert!(!file_to_delete.exists());
 }
 
+#[test]
+fn out_dir_not_owned() {
+	let temp_dir = create_temp_dir("out_dir_not_owned");
+	
+	let file_to_keep = temp_dir.join("file_to_keep");
+	File::create(&file_to_keep).unwrap();
+	
+	let cache_bust = |force| CacheBust::builder()
+		.out_dir(temp_dir.clone())
+		.force(force)
+		.build();
+	
+	// hash_file doesn't clear out_dir, so it can write into it but must not mark it as owned
+	cache_bust(true).hash_file("hello.txt").unwrap();
+	assert!(!temp_dir.join(OUT_DIR_MARKER).exists());
+	
+	let err = cache_bust(false).hash_dir().unwrap_err();
+	assert!(matches!(err, CacheBustError::OutDirNotOwned(out_dir) if out_dir == temp_dir));
+	assert!(file_to_keep.exists());
+	
+	// hash_file marks the directories it creates as owned
+	let out_dir = temp_dir.join("out");
+	let cache_bust = || CacheBust::builder()
+		.out_dir(out_dir.clone())
+		.build();
+	
+	cache_bust().hash_file("hello.txt").unwrap();
+	assert!(out_dir.join(OUT_DIR_MARKER).is_file());
+	cache_bust().hash_dir().unwrap();
+}
+
+#[test]
+fn out_dir_inside_in_dir() {
+	let temp_dir = create_temp_dir("out_dir_inside_in_dir");
+	
+	let err = CacheBust::builder()
+		.in_dir(temp_dir.clone())
+		.out_dir(temp_dir.join("not_yet_created").join("out"))
+		.try_build().unwrap_err();
+	
+	assert!(matches!(err, CacheBustBuilderError::OutDirInsideInDir {..}));
+	
+	let err = CacheBust::builder()
+		.in_dir(temp_dir.clone())
+		.out_dir(temp_dir.join("."))
+		.try_build().unwrap_err();
+	
+	assert!(matches!(err, CacheBustBuilderError::OutDirInsideInDir {..}));
+}
+
+#[test]
+fn in_dir_inside_out_dir() {
+	let temp_dir = create_temp_dir("in_dir_inside_out_dir");
+	let in_dir = temp_dir.join("in");
+	fs::create_dir(&in_dir).unwrap();
+	
+	let err = CacheBust::builder()
+		.in_dir(in_dir.clone())
+		.out_dir(in_dir.join(".."))
+		.try_build().unwrap_err();
+	
+	let CacheBustBuilderError::InDirInsideOutDir {in_dir: canonical_in_dir, out_dir} = err else {
+		panic!("expected InDirInsideOutDir, got {err:?}");
+	};
+	
+	assert_eq!(canonical_in_dir, in_dir.canonicalize().unwrap());
+	assert_eq!(out_dir, temp_dir.canonicalize().unwrap());
+}
+
+#[test]
+fn owned_out_dir_is_cleared() {
+	let temp_dir = create_temp_dir("owned_out_dir_is_cleared");
+	
+	let cache_bust = CacheBust::builder()
+		.out_dir(temp_dir.clone())
+		.build();
+	
+	cache_bust.hash_dir().unwrap();
+	assert!(temp_dir.join(OUT_DIR_MARKER).is_file());
+	
+	let file_to_delete = temp_dir.join("file_to_delete");
+	File::create(&file_to_delete).unwrap();
+	
+	cache_bust.hash_dir().unwrap();
+	assert!(!file_to_delete.exists());
+	assert!(temp_dir.join(OUT_DIR_MARKER).is_file());
+}
+
 #[test]
 fn file_out_dir_not_cleared() {
 	let temp_dir = create_temp_dir("file_out_dir_not_cleared");
@@ -109,7 +199,7 @@ fn absolute_file_to_out_dir() {
 	hi.push("hi.txt");
 	
 	CacheBust::builder()
-		.in_dir(temp_dir.clone())
+		.in_dir(assets_dir().join("images"))
 		.out_dir(temp_dir.clone())
 		.build()
 		.hash_file(hi.canonicalize().unwrap()).unwrap();
@@ -119,6 +209,369 @@ fn absolute_file_to_out_dir() {
 	assert_eq!(fs::read(hi).unwrap(), fs::read(hi_hashed).unwrap());
 }
 
+#[test]
+fn file_with_algorithm() {
+	let temp_dir = create_temp_dir("file_with_algorithm");
+	
+	CacheBust::builder()
+		.out_dir(temp_dir.clone())
+		.hash_algorithm(HashAlgorithm::Xxh3)
+		.build()
+		.hash_file("hello.txt").unwrap();
+	
+	let hello_hashed = temp_dir.join("hello.c3082335d9e644a032c1b16a5ed98ffb.txt");
+	
+	assert_eq!(fs::read(assets_dir().join("hello.txt")).unwrap(), fs::read(hello_hashed).unwrap());
+}
+
+#[test]
+fn file_with_encoding_and_length() {
+	let temp_dir = create_temp_dir("file_with_encoding_and_length");
+	
+	CacheBust::builder()
+		.out_dir(temp_dir.clone())
+		.hash_encoding(HashEncoding::Base64Url)
+		.hash_length(12)
+		.build()
+		.hash_file("hello.txt").unwrap();
+	
+	let hello_hashed = temp_dir.join("hello.2QFMRiSESqW6.txt");
+	
+	assert_eq!(fs::read(assets_dir().join("hello.txt")).unwrap(), fs::read(hello_hashed).unwrap());
+}
+
+#[test]
+fn in_to_out_dir_with_template() {
+	let temp_dir = create_temp_dir("in_to_out_dir_with_template");
+	
+	CacheBust::builder()
+		.out_dir(temp_dir.clone())
+		.hash_length(8)
+		.name_template("{hash}/{stem}.{ext}".parse().unwrap())
+		.build()
+		.hash_dir().unwrap();
+	
+	let hello_hashed = temp_dir.join("d9014c46").join("hello.txt");
+	let hi_hashed = temp_dir.join("greetings").join("c01a4cfa").join("hi.txt");
+	
+	assert_eq!(fs::read(assets_dir().join("hello.txt")).unwrap(), fs::read(hello_hashed).unwrap());
+	assert_eq!(fs::read(assets_dir().join("greetings").join("hi.txt")).unwrap(), fs::read(hi_hashed).unwrap());
+}
+
+#[test]
+fn in_place_with_template() {
+	let temp_dir = create_temp_dir("in_place_with_template");
+	
+	let some_text = temp_dir.join("some_text.txt");
+	fs::write(&some_text, b"Some text").unwrap();
+	
+	CacheBust::builder()
+		.in_dir(temp_dir.clone())
+		.in_place(true)
+		.hash_length(8)
+		.name_template("{stem}-{hash}.{ext}".parse().unwrap())
+		.build()
+		.hash_dir().unwrap();
+	
+	assert!(!some_text.exists());
+	assert_eq!(fs::read(temp_dir.join("some_text-4c2e9e6d.txt")).unwrap(), b"Some text");
+}
+
+#[test]
+fn file_with_compound_extension() {
+	let temp_dir = create_temp_dir("file_with_compound_extension");
+	
+	CacheBust::builder()
+		.out_dir(temp_dir.clone())
+		.build()
+		.hash_file("scripts/app.min.js").unwrap();
+	
+	let app_hashed = temp_dir.join("scripts").join("app.e4f99453fffe7c06cfa40d01d83263562f85ec8ce357dc7dc0e8ce70796c1a7b.min.js");
+	
+	assert_eq!(fs::read(assets_dir().join("scripts").join("app.min.js")).unwrap(), fs::read(app_hashed).unwrap());
+}
+
+#[test]
+fn in_to_out_dir_with_manifest() {
+	let temp_dir = create_temp_dir("in_to_out_dir_with_manifest");
+	let manifest_path = temp_dir.join("manifest").join("assets.json");
+	
+	CacheBust::builder()
+		.out_dir(temp_dir.join("out"))
+		.manifest(&manifest_path)
+		.build()
+		.hash_dir().unwrap();
+	
+	let manifest: Manifest = serde_json::from_str(&fs::read_to_string(manifest_path).unwrap()).unwrap();
+	
+	let hi = &manifest.files["greetings/hi.txt"];
+	assert_eq!(hi.path, "greetings/hi.c01a4cfa25cb895cdd0bb25181ba9c1622e93895a6de6f533a7299f70d6b0cfb.txt");
+	assert_eq!(hi.hash, "c01a4cfa25cb895cdd0bb25181ba9c1622e93895a6de6f533a7299f70d6b0cfb");
+	assert_eq!(hi.size, fs::metadata(assets_dir().join("greetings").join("hi.txt")).unwrap().len());
+	assert_eq!(hi.algorithm, HashAlgorithm::Sha256);
+	
+	assert_eq!(manifest.files.len(), 5);
+	
+	for (original, entry) in manifest.files {
+		assert_eq!(fs::read(assets_dir().join(original)).unwrap(), fs::read(temp_dir.join("out").join(entry.path)).unwrap());
+	}
+}
+
+#[test]
+fn import_map() {
+	let temp_dir = create_temp_dir("import_map");
+	let json_path = temp_dir.join("importmap.json");
+	let html_path = temp_dir.join("importmap.html");
+	
+	let build = |path: &Path| CacheBust::builder()
+		.out_dir(temp_dir.join("out"))
+		.hash_length(8)
+		.import_map(path)
+		.import_map_base("/assets")
+		.build()
+		.hash_dir().unwrap();
+	
+	let report = build(&json_path);
+	let script = report.assets.iter().find(|asset| asset.relative_path == Path::new("generated").join("script.js")).unwrap();
+	
+	let import_map: ImportMap = serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+	assert_eq!(import_map.imports.len(), 2);
+	assert_eq!(import_map.imports["/assets/scripts/app.min.js"], "/assets/scripts/app.e4f99453.min.js");
+	assert_eq!(import_map.imports["/assets/generated/script.js"], format!("/assets/{}", script.url_path()));
+	
+	build(&html_path);
+	assert_eq!(fs::read_to_string(&html_path).unwrap(), format!("<script type=\"importmap\">\n{}\n</script>\n", import_map.to_json()));
+	
+	// modules are detected regardless of the case of their extension
+	let mut manifest = Manifest::from_assets([script]);
+	let entry = manifest.files["generated/script.js"].clone();
+	manifest.files.insert("generated/LEGACY.MJS".to_owned(), entry);
+	assert_eq!(manifest.to_import_map("/").imports.len(), 2);
+}
+
+#[test]
+fn hashed_assets() {
+	let temp_dir = create_temp_dir("hashed_assets");
+	
+	let assets = CacheBust::builder()
+		.out_dir(temp_dir.clone())
+		.name_template("{stem}.{ext}?v={hash}".parse().unwrap())
+		.hash_length(8)
+		.build()
+		.hash_dir().unwrap()
+		.assets;
+	
+	assert_eq!(assets.len(), 5);
+	
+	let hi = assets.iter().find(|asset| asset.relative_path == Path::new("greetings").join("hi.txt")).unwrap();
+	assert_eq!(hi.source, assets_dir().join("greetings").join("hi.txt"));
+	assert_eq!(hi.dest, temp_dir.join("greetings").join("hi.txt"));
+	assert_eq!(hi.hashed_relative_path, Path::new("greetings").join("hi.txt"));
+	assert_eq!(hi.hashed_name, "hi.txt?v=c01a4cfa");
+	assert_eq!(hi.url_path(), "greetings/hi.txt?v=c01a4cfa");
+	assert_eq!(hi.hash, "c01a4cfa");
+	assert_eq!(hi.algorithm, HashAlgorithm::Sha256);
+	assert_eq!(hi.len, fs::metadata(&hi.source).unwrap().len());
+	
+	for asset in assets {
+		assert_eq!(fs::read(asset.source).unwrap(), fs::read(asset.dest).unwrap());
+	}
+}
+
+#[test]
+fn hashed_asset_from_file() {
+	let temp_dir = create_temp_dir("hashed_asset_from_file");
+	
+	let asset = CacheBust::builder()
+		.out_dir(temp_dir.clone())
+		.build()
+		.hash_file("scripts/app.min.js").unwrap();
+	
+	let hashed_name = "app.e4f99453fffe7c06cfa40d01d83263562f85ec8ce357dc7dc0e8ce70796c1a7b.min.js";
+	assert_eq!(asset.relative_path, Path::new("scripts").join("app.min.js"));
+	assert_eq!(asset.hashed_relative_path, Path::new("scripts").join(hashed_name));
+	assert_eq!(asset.dest, temp_dir.join("scripts").join(hashed_name));
+	assert_eq!(asset.hash, "e4f99453fffe7c06cfa40d01d83263562f85ec8ce357dc7dc0e8ce70796c1a7b");
+}
+
+#[test]
+fn in_to_out_dir_with_lookup_module() {
+	let temp_dir = create_temp_dir("in_to_out_dir_with_lookup_module");
+	let lookup_module = temp_dir.join("assets.rs");
+	
+	CacheBust::builder()
+		.out_dir(temp_dir.join("out"))
+		.lookup_module(&lookup_module)
+		.build()
+		.hash_dir().unwrap();
+	
+	let module = fs::read_to_string(lookup_module).unwrap();
+	
+	assert!(module.contains("pub static ASSETS: ::cache_bust::AssetMap = ::cache_bust::AssetMap::new(&[\n"));
+	assert!(module.contains("\t(\"greetings/hi.txt\", \"greetings/hi.c01a4cfa25cb895cdd0bb25181ba9c1622e93895a6de6f533a7299f70d6b0cfb.txt\"),\n"));
+	assert_eq!(module.matches("\t(").count(), 5);
+}
+
+mod generated_assets {
+	include!("fixtures/assets.rs");
+}
+
+#[test]
+fn hash_file_with_lookup_module() {
+	let temp_dir = create_temp_dir("hash_file_with_lookup_module");
+	let lookup_module = temp_dir.join("assets.rs");
+	
+	let cache_bust = CacheBust::builder()
+		.out_dir(temp_dir.join("out"))
+		.hash_length(8)
+		.lookup_module(&lookup_module)
+		.build();
+	
+	let script = cache_bust.hash_file("generated/script.js").unwrap();
+	let module = fs::read_to_string(&lookup_module).unwrap();
+	assert_eq!(module.matches("\t(").count(), 1);
+	
+	// files hashed later are added to the ones before
+	cache_bust.hash_file("hello.txt").unwrap();
+	let module = fs::read_to_string(&lookup_module).unwrap();
+	assert_eq!(module, include_str!("fixtures/assets.rs"));
+	
+	assert_eq!(generated_assets::ASSETS.lookup("generated/script.js"), Some(script.url_path().as_str()));
+	assert_eq!(generated_assets::ASSETS.lookup("/hello.txt"), Some("hello.d9014c46.txt"));
+	assert_eq!(generated_assets::lookup("hello.txt"), Some("hello.d9014c46.txt"));
+	assert_eq!(generated_assets::lookup("missing.txt"), None);
+}
+
+#[test]
+fn asset_map_lookup() {
+	static ASSETS: AssetMap = AssetMap::new(&[
+		("greetings/hi.txt", "greetings/hi.c01a4cfa.txt"),
+		("hello.txt", "hello.d9014c46.txt"),
+	]);
+	
+	assert_eq!(ASSETS.lookup("hello.txt"), Some("hello.d9014c46.txt"));
+	assert_eq!(ASSETS.lookup("/greetings/hi.txt"), Some("greetings/hi.c01a4cfa.txt"));
+	assert_eq!(ASSETS.lookup("missing.txt"), None);
+	assert_eq!(ASSETS.len(), 2);
+}
+
+#[test]
+fn in_to_out_dir_with_filters() {
+	let temp_dir = create_temp_dir("in_to_out_dir_with_filters");
+	
+	let assets = CacheBust::builder()
+		.out_dir(temp_dir.clone())
+		.include("**/*.txt")
+		.exclude("greetings/*")
+		.exclude_with("hello.txt", ExcludePolicy::CopyUnhashed)
+		.exclude_with("**/*.js", ExcludePolicy::CopyUnhashed)
+		.build()
+		.hash_dir().unwrap()
+		.assets;
+	
+	assert!(assets.is_empty());
+	assert_eq!(fs::read(assets_dir().join("hello.txt")).unwrap(), fs::read(temp_dir.join("hello.txt")).unwrap());
+	assert_eq!(
+		fs::read(assets_dir().join("scripts").join("app.min.js")).unwrap(),
+		fs::read(temp_dir.join("scripts").join("app.min.js")).unwrap()
+	);
+	assert!(!temp_dir.join("greetings").exists());
+	assert!(!temp_dir.join("images").exists());
+	
+	let assets = CacheBust::builder()
+		.out_dir(temp_dir.clone())
+		.include("**/*.txt")
+		.build()
+		.hash_dir().unwrap()
+		.assets;
+	
+	let mut hashed: Vec<_> = assets.into_iter().map(|asset| asset.relative_path).collect();
+	hashed.sort();
+	assert_eq!(hashed, [Path::new("greetings").join("hi.txt"), PathBuf::from("hello.txt")]);
+}
+
+#[test]
+fn invalid_glob() {
+	let err = CacheBust::builder()
+		.out_dir(create_temp_dir("invalid_glob"))
+		.exclude("[a-")
+		.try_build().unwrap_err();
+	
+	assert!(matches!(err, CacheBustBuilderError::InvalidGlob(_)));
+}
+
+#[test]
+fn in_to_out_dir_incremental() {
+	let temp_dir = create_temp_dir("in_to_out_dir_incremental");
+	let in_dir = temp_dir.join("in");
+	let out_dir = temp_dir.join("out");
+	fs::create_dir(&in_dir).unwrap();
+	fs::write(in_dir.join("a.txt"), "a").unwrap();
+	fs::write(in_dir.join("b.txt"), "b").unwrap();
+	fs::write(in_dir.join("index.html"), "index").unwrap();
+	
+	let cache_bust = CacheBust::builder()
+		.in_dir(in_dir.clone())
+		.out_dir(out_dir.clone())
+		.hash_length(8)
+		.exclude_with("index.html", ExcludePolicy::CopyUnhashed)
+		.incremental(true)
+		.build();
+	
+	let report = cache_bust.hash_dir().unwrap();
+	assert_eq!(report.written().count(), 2);
+	assert!(report.removed.is_empty());
+	
+	fs::write(in_dir.join("b.txt"), "changed").unwrap();
+	fs::write(out_dir.join("unrelated.txt"), "stale").unwrap();
+	
+	let report = cache_bust.hash_dir().unwrap();
+	let status = |name: &str| report.assets.iter()
+		.find(|asset| asset.relative_path == Path::new(name))
+		.unwrap()
+		.status;
+	
+	assert_eq!(status("a.txt"), AssetStatus::Unchanged);
+	assert_eq!(status("b.txt"), AssetStatus::Written);
+	
+	let mut removed = report.removed.clone();
+	removed.sort();
+	assert_eq!(removed, [out_dir.join("b.3e23e816.txt"), out_dir.join("unrelated.txt")]);
+	
+	let mut files: Vec<_> = fs::read_dir(&out_dir).unwrap()
+		.map(|entry| entry.unwrap().file_name().into_string().unwrap())
+		.collect();
+	files.sort();
+	assert_eq!(files, [".cache_bust", "a.ca978112.txt", "b.d67e2e94.txt", "index.html"]);
+}
+
+#[test]
+fn in_to_out_dir_incremental_same_length() {
+	let temp_dir = create_temp_dir("in_to_out_dir_incremental_same_length");
+	let in_dir = temp_dir.join("in");
+	let out_dir = temp_dir.join("out");
+	fs::create_dir(&in_dir).unwrap();
+	fs::write(in_dir.join("x.txt"), "AAAA").unwrap();
+	
+	// the destination name doesn't change with the contents
+	let cache_bust = CacheBust::builder()
+		.in_dir(in_dir.clone())
+		.out_dir(out_dir.clone())
+		.name_template("{stem}.{ext}?v={hash}".parse().unwrap())
+		.incremental(true)
+		.build();
+	
+	cache_bust.hash_dir().unwrap();
+	fs::write(in_dir.join("x.txt"), "BBBB").unwrap();
+	
+	let report = cache_bust.hash_dir().unwrap();
+	assert_eq!(report.assets[0].status, AssetStatus::Written);
+	assert_eq!(fs::read_to_string(out_dir.join("x.txt")).unwrap(), "BBBB");
+	
+	let report = cache_bust.hash_dir().unwrap();
+	assert_eq!(report.assets[0].status, AssetStatus::Unchanged);
+}
+
 #[test]
 fn in_place() {
 	let temp_dir = create_temp_dir("in_place");
@@ -146,6 +599,61 @@ fn in_place() {
 	assert_eq!(fs::read(some_text).unwrap(), b"Some text");
 }
 
+#[test]
+fn in_place_twice() {
+	let temp_dir = create_temp_dir("in_place_twice");
+	fs::create_dir(temp_dir.join("texts")).unwrap();
+	fs::write(temp_dir.join("texts").join("some_text.txt"), b"Some text").unwrap();
+	File::create(temp_dir.join("empty")).unwrap();
+	
+	let cache_bust = CacheBust::builder()
+		.in_dir(temp_dir.clone())
+		.in_place(true)
+		.hash_length(8)
+		.build();
+	
+	let first = cache_bust.hash_dir().unwrap();
+	assert_eq!(first.written().count(), 2);
+	
+	let second = cache_bust.hash_dir().unwrap();
+	assert_eq!(second.written().count(), 0);
+	
+	let mut first = first.assets;
+	let mut second = second.assets;
+	first.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+	second.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+	
+	for (first, second) in first.iter().zip(&second) {
+		assert_eq!(first.relative_path, second.relative_path);
+		assert_eq!(first.dest, second.dest);
+		assert_eq!(first.hashed_name, second.hashed_name);
+		assert_eq!(second.status, AssetStatus::Unchanged);
+	}
+	
+	assert_eq!(fs::read(temp_dir.join("texts").join("some_text.4c2e9e6d.txt")).unwrap(), b"Some text");
+	assert_eq!(fs::read(temp_dir.join("empty.e3b0c442")).unwrap(), b"");
+}
+
+#[test]
+fn in_place_twice_with_template() {
+	let temp_dir = create_temp_dir("in_place_twice_with_template");
+	fs::write(temp_dir.join("some_text.txt"), b"Some text").unwrap();
+	
+	let cache_bust = CacheBust::builder()
+		.in_dir(temp_dir.clone())
+		.in_place(true)
+		.hash_length(8)
+		.name_template("{hash}/{stem}.{ext}".parse().unwrap())
+		.build();
+	
+	cache_bust.hash_dir().unwrap();
+	let report = cache_bust.hash_dir().unwrap();
+	
+	assert_eq!(report.assets[0].relative_path, Path::new("some_text.txt"));
+	assert_eq!(report.assets[0].status, AssetStatus::Unchanged);
+	assert_eq!(fs::read(temp_dir.join("4c2e9e6d").join("some_text.txt")).unwrap(), b"Some text");
+}
+
 #[test]
 fn in_place_with_out_dir() {
 	let temp_dir = create_temp_dir("in_place_with_out_dir");
@@ -237,3 +745,574 @@ fn in_dir_not_a_dir() {
 		.in_place(true)
 		.build();
 }
+
+#[test]
+#[should_panic(expected = "hash length 4 is too short")]
+fn hash_length_too_short() {
+	CacheBust::builder()
+		.in_place(true)
+		.hash_length(4)
+		.build();
+}
+
+#[test]
+fn missing_file_error() {
+	let temp_dir = create_temp_dir("missing_file_error");
+	
+	let err = CacheBust::builder()
+		.out_dir(temp_dir)
+		.build()
+		.hash_file("missing.txt").unwrap_err();
+	
+	assert!(matches!(err, CacheBustError::Io {operation: Operation::Hash, ..}));
+	assert_eq!(err.path(), Some(assets_dir().join("missing.txt").as_path()));
+}
+
+#[test]
+fn builder_error_converts() {
+	fn build() -> Result<CacheBust, CacheBustError> {
+		Ok(CacheBust::builder().try_build()?)
+	}
+	
+	let err = build().unwrap_err();
+	
+	assert!(matches!(err, CacheBustError::Build(_)));
+	assert_eq!(err.to_string(), "out_dir must be specified or in_place set to true");
+}
+
+fn css_test_dir(test: &'static str, css: &str) -> PathBuf {
+	let temp_dir = create_temp_dir(test);
+	let in_dir = temp_dir.join("in");
+	fs::create_dir_all(in_dir.join("images")).unwrap();
+	fs::create_dir_all(in_dir.join("css")).unwrap();
+	fs::copy(assets_dir().join("images").join("circle.png"), in_dir.join("images").join("circle.png")).unwrap();
+	fs::write(in_dir.join("css").join("style.css"), css).unwrap();
+	temp_dir
+}
+
+fn hash_str(contents: &str) -> String {
+	Hasher::new(HashOptions::default().hash_length(8).unwrap())
+		.hash_reader(contents.as_bytes()).unwrap()
+}
+
+#[test]
+fn rewrite_css() {
+	let temp_dir = css_test_dir("rewrite_css", r#"
+		@import "/images/circle.png";
+		body { background: url(../images/circle.png); }
+		.a { background: url( "/images/circle.png?x=1#frag" ); }
+		.b { background: url('data:image/png;base64,AAAA'); }
+		.c { background: url(https://example.com/circle.png); }
+		/* url(../images/circle.png) */
+		.d::after { content: "url(../images/circle.png)"; }
+		.e { background: url(missing.png); }
+	"#);
+	
+	let report = CacheBust::builder()
+		.in_dir(temp_dir.join("in"))
+		.out_dir(temp_dir.join("out"))
+		.hash_length(8)
+		.rewrite_css(true)
+		.build()
+		.hash_dir().unwrap();
+	
+	let expected = r#"
+		@import "/images/circle.f04a632b.png";
+		body { background: url(../images/circle.f04a632b.png); }
+		.a { background: url( "/images/circle.f04a632b.png?x=1#frag" ); }
+		.b { background: url('data:image/png;base64,AAAA'); }
+		.c { background: url(https://example.com/circle.png); }
+		/* url(../images/circle.png) */
+		.d::after { content: "url(../images/circle.png)"; }
+		.e { background: url(missing.png); }
+	"#;
+	
+	let style = report.assets.iter().find(|asset| asset.relative_path == Path::new("css").join("style.css")).unwrap();
+	assert_eq!(style.hash, hash_str(expected));
+	assert_eq!(style.len, expected.len() as u64);
+	assert_eq!(fs::read_to_string(&style.dest).unwrap(), expected);
+}
+
+#[test]
+fn rewrite_css_differs_from_macro() {
+	let css = "body { background: url(../images/circle.png); }";
+	let temp_dir = css_test_dir("rewrite_css_differs_from_macro", css);
+	let in_dir = temp_dir.join("in");
+	fs::write(in_dir.join("css").join("plain.css"), "body {}").unwrap();
+	
+	let report = CacheBust::builder()
+		.in_dir(&in_dir)
+		.out_dir(temp_dir.join("out"))
+		.hash_length(8)
+		.rewrite_css(true)
+		.build()
+		.hash_dir().unwrap();
+	
+	// the name the asset! macro would produce, as it hashes the original file
+	let macro_name = |name: &str| {
+		let options = HashOptions::default().hash_length(8).unwrap();
+		cache_bust_core::hashed_file_name_with(&in_dir.join("css").join(name), &options).unwrap()
+	};
+	let asset = |name: &str| report.assets.iter().find(|asset| asset.relative_path == Path::new("css").join(name)).unwrap();
+	
+	let style = asset("style.css");
+	assert_ne!(style.hashed_name, macro_name("style.css"));
+	assert!(!temp_dir.join("out").join("css").join(macro_name("style.css")).exists());
+	
+	// files without references to rewrite keep the name produced by the macro
+	assert_eq!(asset("plain.css").hashed_name, macro_name("plain.css"));
+}
+
+#[test]
+fn rewrite_css_with_template() {
+	let temp_dir = css_test_dir("rewrite_css_with_template", "body { background: url(../images/circle.png?x=1); }");
+	
+	let report = CacheBust::builder()
+		.in_dir(temp_dir.join("in"))
+		.out_dir(temp_dir.join("out"))
+		.hash_length(8)
+		.name_template("{hash}/{stem}.{ext}".parse().unwrap())
+		.rewrite_css(true)
+		.build()
+		.hash_dir().unwrap();
+	
+	let expected = "body { background: url(../../images/f04a632b/circle.png?x=1); }";
+	let style = report.assets.iter().find(|asset| asset.relative_path == Path::new("css").join("style.css")).unwrap();
+	assert_eq!(fs::read_to_string(&style.dest).unwrap(), expected);
+	assert_eq!(style.dest, temp_dir.join("out").join("css").join(hash_str(expected)).join("style.css"));
+}
+
+#[test]
+fn rewrite_css_in_place_twice() {
+	let temp_dir = css_test_dir("rewrite_css_in_place_twice", "body { background: url(../images/circle.png); }");
+	
+	let cache_bust = CacheBust::builder()
+		.in_dir(temp_dir.join("in"))
+		.in_place(true)
+		.hash_length(8)
+		.rewrite_css(true)
+		.build();
+	
+	cache_bust.hash_dir().unwrap();
+	let report = cache_bust.hash_dir().unwrap();
+	
+	let expected = "body { background: url(../images/circle.f04a632b.png); }";
+	let style = temp_dir.join("in").join("css").join(format!("style.{}.css", hash_str(expected)));
+	assert_eq!(fs::read_to_string(style).unwrap(), expected);
+	assert_eq!(report.written().count(), 0);
+	assert!(!temp_dir.join("in").join("css").join("style.css").exists());
+}
+
+#[test]
+fn rewrite_css_in_dependency_order() {
+	let temp_dir = css_test_dir("rewrite_css_in_dependency_order", "@import url(theme/colors.css);");
+	let theme = temp_dir.join("in").join("css").join("theme");
+	fs::create_dir(&theme).unwrap();
+	fs::write(theme.join("colors.css"), "body { background: url(/images/circle.png); }").unwrap();
+	
+	let cache_bust = CacheBust::builder()
+		.in_dir(temp_dir.join("in"))
+		.out_dir(temp_dir.join("out"))
+		.hash_length(8)
+		.rewrite_css(true)
+		.build();
+	
+	let hashes = || {
+		let report = cache_bust.hash_dir().unwrap();
+		let hash = |path: &Path| report.assets.iter().find(|asset| asset.relative_path == path).unwrap().hash.clone();
+		(hash(&Path::new("css").join("style.css")), hash(&Path::new("css").join("theme").join("colors.css")))
+	};
+	
+	let (style, colors) = hashes();
+	let expected_colors = "body { background: url(/images/circle.f04a632b.png); }";
+	let expected_style = format!("@import url(theme/colors.{}.css);", hash_str(expected_colors));
+	assert_eq!(colors, hash_str(expected_colors));
+	assert_eq!(style, hash_str(&expected_style));
+	
+	fs::write(temp_dir.join("in").join("images").join("circle.png"), b"changed").unwrap();
+	let (changed_style, changed_colors) = hashes();
+	assert_ne!(changed_colors, colors);
+	assert_ne!(changed_style, style);
+}
+
+#[test]
+fn rewrite_css_reference_cycle() {
+	let temp_dir = css_test_dir("rewrite_css_reference_cycle", "@import 'a.css'; .self { background: url(style.css); }");
+	let css = temp_dir.join("in").join("css");
+	fs::write(css.join("a.css"), "@import 'b.css';").unwrap();
+	fs::write(css.join("b.css"), "@import '/css/style.css';").unwrap();
+	
+	let err = CacheBust::builder()
+		.in_dir(temp_dir.join("in"))
+		.out_dir(temp_dir.join("out"))
+		.rewrite_css(true)
+		.build()
+		.hash_dir().unwrap_err();
+	
+	let CacheBustError::ReferenceCycle(mut cycle) = err else {
+		panic!("expected a reference cycle, got {err:?}");
+	};
+	
+	cycle.sort();
+	assert_eq!(cycle, [css.join("a.css"), css.join("b.css"), css.join("style.css")]);
+}
+
+#[test]
+fn rewrite_html_entry() {
+	let temp_dir = css_test_dir("rewrite_html_entry", "body {}");
+	let index = r##"<!DOCTYPE html>
+<html>
+<head>
+	<meta charset="utf-8">
+	<meta name="viewport" content="width=device-width">
+	<meta property="og:image" content="/images/circle.png">
+	<link rel="stylesheet" href="css/style.css">
+	<style>body { background: url('images/circle.png'); }</style>
+	<script>const img = "<img src='images/circle.png'>";</script>
+</head>
+<body>
+	<!-- <img src="images/circle.png"> -->
+	<img src=images/circle.png srcset="images/circle.png 1x, /images/circle.png?a=b 2x" alt="circle">
+	<video poster='images/circle.png'></video>
+	<a href="https://example.com/images/circle.png">link</a>
+	<a href="#top" style="background: url(images/circle.png)">top</a>
+</body>
+</html>
+"##;
+	fs::write(temp_dir.join("in").join("index.html"), index).unwrap();
+	
+	let report = CacheBust::builder()
+		.in_dir(temp_dir.join("in"))
+		.out_dir(temp_dir.join("out"))
+		.hash_length(8)
+		.entry("*.html")
+		.build()
+		.hash_dir().unwrap();
+	
+	assert_eq!(report.assets.len(), 2);
+	
+	let expected = index
+		.replace("content=\"/images/circle.png\"", "content=\"/images/circle.f04a632b.png\"")
+		.replace("css/style.css", &format!("css/style.{}.css", hash_str("body {}")))
+		.replace("url('images/circle.png')", "url('images/circle.f04a632b.png')")
+		.replace("src=images/circle.png", "src=images/circle.f04a632b.png")
+		.replace("\"images/circle.png 1x, /images/circle.png?a=b 2x\"", "\"images/circle.f04a632b.png 1x, /images/circle.f04a632b.png?a=b 2x\"")
+		.replace("poster='images/circle.png'", "poster='images/circle.f04a632b.png'")
+		.replace("url(images/circle.png)", "url(images/circle.f04a632b.png)");
+	
+	assert_eq!(fs::read_to_string(temp_dir.join("out").join("index.html")).unwrap(), expected);
+}
+
+#[test]
+fn rewrite_js() {
+	let temp_dir = css_test_dir("rewrite_js", "body {}");
+	let js = temp_dir.join("in").join("js");
+	fs::create_dir(&js).unwrap();
+	fs::write(js.join("util.js"), "export const url = new URL('../images/circle.png', import.meta.url);").unwrap();
+	fs::write(js.join("lazy.mjs"), "export default 1;").unwrap();
+	
+	let app = r#"import { url } from "./util.js";
+import "../css/style.css";
+import { html } from "lit";
+export * from './lazy.mjs';
+// import "./util.js";
+const text = "import './util.js'";
+const template = `${"./util.js"} import("./lazy.mjs")`;
+const ratio = 1 / 2, regex = /import "\.\/util.js"/g;
+const lazy = await import("./lazy.mjs");
+const dynamic = await import(`./${name}.js`);
+const worker = new URL("./worker.js", import.meta.url);
+const other = new URL("./util.js", location.href);
+"#;
+	fs::write(js.join("app.js"), app).unwrap();
+	
+	let report = CacheBust::builder()
+		.in_dir(temp_dir.join("in"))
+		.out_dir(temp_dir.join("out"))
+		.hash_length(8)
+		.rewrite_js(true)
+		.build()
+		.hash_dir().unwrap();
+	
+	let expected_util = "export const url = new URL('../images/circle.f04a632b.png', import.meta.url);";
+	let util = format!("./util.{}.js", hash_str(expected_util));
+	let lazy = format!("./lazy.{}.mjs", hash_str("export default 1;"));
+	let expected_app = app
+		.replace("\"./util.js\";\nimport", &format!("\"{util}\";\nimport"))
+		.replace("../css/style.css", &format!("../css/style.{}.css", hash_str("body {}")))
+		.replace("'./lazy.mjs'", &format!("'{lazy}'"))
+		.replace("import(\"./lazy.mjs\");", &format!("import(\"{lazy}\");"));
+	
+	let asset = |path: &Path| report.assets.iter().find(|asset| asset.relative_path == path).unwrap();
+	assert_eq!(asset(&Path::new("js").join("util.js")).hash, hash_str(expected_util));
+	
+	let app = asset(&Path::new("js").join("app.js"));
+	assert_eq!(fs::read_to_string(&app.dest).unwrap(), expected_app);
+	assert_eq!(app.hash, hash_str(&expected_app));
+}
+
+const BUNDLE_JS: &str = "console.log(1);\n//# sourceMappingURL=bundle.js.map\n";
+const BUNDLE_JS_MAP: &str = r#"{"version":3,"file":"bundle.js","sources":["../src/main.ts","../images/circle.png"],"names":[],"mappings":"AAAA"}"#;
+
+fn source_map_test_dir(test: &'static str) -> PathBuf {
+	let temp_dir = css_test_dir(test, "body {}\n/*# sourceMappingURL=style.css.map */");
+	let js = temp_dir.join("in").join("js");
+	fs::create_dir(&js).unwrap();
+	fs::write(js.join("bundle.js"), BUNDLE_JS).unwrap();
+	fs::write(js.join("bundle.js.map"), BUNDLE_JS_MAP).unwrap();
+	temp_dir
+}
+
+#[test]
+fn source_maps_rewrite() {
+	let temp_dir = source_map_test_dir("source_maps_rewrite");
+	
+	let report = CacheBust::builder()
+		.in_dir(temp_dir.join("in"))
+		.out_dir(temp_dir.join("out"))
+		.hash_length(8)
+		.source_maps(SourceMapPolicy::Rewrite)
+		.build()
+		.hash_dir().unwrap();
+	
+	let bundle_hash = hash_str("console.log(1);\n//# sourceMappingURL=\n");
+	let expected_map = format!(
+		r#"{{"file":"bundle.{bundle_hash}.js","mappings":"AAAA","names":[],"sources":["../src/main.ts","../images/circle.f04a632b.png"],"version":3}}"#,
+	);
+	let map_name = format!("bundle.{}.js.map", hash_str(&expected_map));
+	
+	let asset = |path: &Path| report.assets.iter().find(|asset| asset.relative_path == path).unwrap();
+	let bundle = asset(&Path::new("js").join("bundle.js"));
+	let map = asset(&Path::new("js").join("bundle.js.map"));
+	
+	assert_eq!(bundle.hash, bundle_hash);
+	assert_eq!(fs::read_to_string(&bundle.dest).unwrap(), format!("console.log(1);\n//# sourceMappingURL={map_name}\n"));
+	assert_eq!(map.hashed_name, map_name.as_str());
+	assert_eq!(fs::read_to_string(&map.dest).unwrap(), expected_map);
+	
+	// the stylesheet references a source map that doesn't exist
+	let style = asset(&Path::new("css").join("style.css"));
+	assert_eq!(fs::read_to_string(&style.dest).unwrap(), "body {}\n/*# sourceMappingURL=style.css.map */");
+	assert_eq!(report.assets.len(), 4);
+}
+
+#[test]
+fn source_maps_rewrite_with_template() {
+	let temp_dir = source_map_test_dir("source_maps_rewrite_with_template");
+	
+	let report = CacheBust::builder()
+		.in_dir(temp_dir.join("in"))
+		.out_dir(temp_dir.join("out"))
+		.hash_length(8)
+		.name_template("{hash}/{stem}.{ext}".parse().unwrap())
+		.source_maps(SourceMapPolicy::Rewrite)
+		.build()
+		.hash_dir().unwrap();
+	
+	let asset = |path: &Path| report.assets.iter().find(|asset| asset.relative_path == path).unwrap();
+	let bundle = asset(&Path::new("js").join("bundle.js"));
+	let map = asset(&Path::new("js").join("bundle.js.map"));
+	let map: serde_json::Value = serde_json::from_str(&fs::read_to_string(&map.dest).unwrap()).unwrap();
+	
+	// sources which aren't assets are moved one directory up along with the map
+	assert_eq!(map["file"], format!("../{}/bundle.js", bundle.hash));
+	assert_eq!(map["sources"], serde_json::json!(["../../src/main.ts", "../../images/f04a632b/circle.png"]));
+}
+
+#[test]
+fn source_maps_rewrite_in_place_twice() {
+	let temp_dir = source_map_test_dir("source_maps_rewrite_in_place_twice");
+	
+	let cache_bust = CacheBust::builder()
+		.in_dir(temp_dir.join("in"))
+		.in_place(true)
+		.hash_length(8)
+		.source_maps(SourceMapPolicy::Rewrite)
+		.build();
+	
+	let first = cache_bust.hash_dir().unwrap();
+	let second = cache_bust.hash_dir().unwrap();
+	
+	assert_eq!(second.written().count(), 0);
+	assert_eq!(fs::read_dir(temp_dir.join("in").join("js")).unwrap().count(), 2);
+	
+	for asset in first.assets {
+		assert!(asset.dest.is_file());
+		assert!(!asset.source.exists());
+	}
+}
+
+/// Computes the integrity string of the file at `path` independently of its name hash.
+fn integrity_of(path: &Path) -> String {
+	Hasher::new(HashOptions::default().integrity(IntegrityAlgorithm::Sha384))
+		.hash_file_with_integrity(path).unwrap()
+		.integrity.unwrap()
+}
+
+#[test]
+fn integrity() {
+	let temp_dir = source_map_test_dir("integrity");
+	let manifest_path = temp_dir.join("assets.json");
+	
+	let report = CacheBust::builder()
+		.in_dir(temp_dir.join("in"))
+		.out_dir(temp_dir.join("out"))
+		.hash_length(8)
+		.manifest(&manifest_path)
+		.source_maps(SourceMapPolicy::Rewrite)
+		.integrity(IntegrityAlgorithm::Sha384)
+		.build()
+		.hash_dir().unwrap();
+	
+	let manifest: Manifest = serde_json::from_str(&fs::read_to_string(&manifest_path).unwrap()).unwrap();
+	
+	// the integrity of a script paired with its source map covers the rewritten sourceMappingURL
+	for asset in &report.assets {
+		let integrity = asset.integrity.as_deref().unwrap();
+		assert!(integrity.starts_with("sha384-"));
+		assert_eq!(integrity, integrity_of(&asset.dest));
+		assert_eq!(manifest.files[&asset.relative_path.to_str().unwrap().replace('\\', "/")].integrity.as_deref(), Some(integrity));
+	}
+	
+	// files which are already hashed keep their integrity when hashing in-place again
+	let cache_bust = CacheBust::builder()
+		.in_dir(temp_dir.join("in"))
+		.in_place(true)
+		.hash_length(8)
+		.source_maps(SourceMapPolicy::Rewrite)
+		.integrity(IntegrityAlgorithm::Sha384)
+		.build();
+	
+	let first = cache_bust.hash_dir().unwrap();
+	let second = cache_bust.hash_dir().unwrap();
+	
+	assert_eq!(second.written().count(), 0);
+	
+	for asset in &second.assets {
+		assert_eq!(asset.integrity.as_deref(), Some(integrity_of(&asset.dest).as_str()));
+	}
+	
+	let integrities = |assets: &[cache_bust::HashedAsset]| {
+		let mut integrities: Vec<_> = assets.iter().map(|asset| asset.integrity.clone()).collect();
+		integrities.sort();
+		integrities
+	};
+	assert_eq!(integrities(&first.assets), integrities(&second.assets));
+	
+	// no integrity strings are computed by default
+	let asset = CacheBust::builder()
+		.in_dir(assets_dir())
+		.out_dir(temp_dir.join("unset"))
+		.build()
+		.hash_file(assets_dir().join("images").join("circle.png")).unwrap();
+	assert_eq!(asset.integrity, None);
+	assert!(!serde_json::to_string(&Manifest::from_assets([&asset])).unwrap().contains("integrity"));
+}
+
+#[test]
+fn source_maps_drop() {
+	let temp_dir = source_map_test_dir("source_maps_drop");
+	
+	let report = CacheBust::builder()
+		.in_dir(temp_dir.join("in"))
+		.out_dir(temp_dir.join("out"))
+		.hash_length(8)
+		.source_maps(SourceMapPolicy::Drop)
+		.build()
+		.hash_dir().unwrap();
+	
+	let bundle = report.assets.iter().find(|asset| asset.relative_path == Path::new("js").join("bundle.js")).unwrap();
+	assert_eq!(fs::read_to_string(&bundle.dest).unwrap(), "console.log(1);\n");
+	assert_eq!(bundle.hash, hash_str("console.log(1);\n"));
+	
+	assert_eq!(report.assets.len(), 3);
+	assert_eq!(fs::read_dir(temp_dir.join("out").join("js")).unwrap().count(), 1);
+}
+
+#[test]
+fn precompress() {
+	let temp_dir = create_temp_dir("precompress");
+	let in_dir = temp_dir.join("in");
+	fs::create_dir(&in_dir).unwrap();
+	fs::write(in_dir.join("app.js"), "console.log('hello');\n".repeat(100)).unwrap();
+	fs::write(in_dir.join("small.js"), "console.log('hello');\n").unwrap();
+	fs::copy(assets_dir().join("images").join("circle.png"), in_dir.join("circle.png")).unwrap();
+	
+	// pseudo-random bytes which can't be compressed
+	let mut state = 1u32;
+	let noise: Vec<u8> = (0..2048).map(|_| {
+		state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+		(state >> 16) as u8
+	}).collect();
+	fs::write(in_dir.join("noise.txt"), &noise).unwrap();
+	
+	let manifest_path = temp_dir.join("assets.json");
+	
+	let report = CacheBust::builder()
+		.in_dir(&in_dir)
+		.out_dir(temp_dir.join("out"))
+		.hash_length(8)
+		.manifest(&manifest_path)
+		.precompress(Compression::Brotli)
+		.precompress_with_level(Compression::Gzip, 6)
+		.precompress(Compression::Zstd)
+		.build()
+		.hash_dir().unwrap();
+	
+	let asset = |name: &str| report.assets.iter().find(|asset| asset.relative_path == Path::new(name)).unwrap();
+	
+	let app = asset("app.js");
+	let compressions: Vec<_> = app.compressed.iter().map(|compressed| compressed.compression).collect();
+	assert_eq!(compressions, [Compression::Brotli, Compression::Gzip, Compression::Zstd]);
+	
+	for compressed in &app.compressed {
+		let contents = fs::read(&compressed.dest).unwrap();
+		assert_eq!(compressed.dest.file_name().unwrap(), format!("app.{}.js.{}", app.hash, compressed.compression.extension()).as_str());
+		assert_eq!(contents.len() as u64, compressed.len);
+		assert!(compressed.len < app.len / 10);
+	}
+	
+	assert!(fs::read(temp_dir.join("out").join(format!("app.{}.js.gz", app.hash))).unwrap().starts_with(&[0x1f, 0x8b]));
+	assert!(asset("small.js").compressed.is_empty());
+	assert!(asset("circle.png").compressed.is_empty());
+	assert!(asset("noise.txt").compressed.is_empty());
+	assert_eq!(fs::read_dir(temp_dir.join("out")).unwrap().count(), 8);
+	
+	let manifest: Manifest = serde_json::from_str(&fs::read_to_string(manifest_path).unwrap()).unwrap();
+	let br = &manifest.files["app.js"].compressed["br"];
+	assert_eq!(br.path, format!("app.{}.js.br", app.hash));
+	assert_eq!(br.size, app.compressed[0].len);
+	assert!(manifest.files["small.js"].compressed.is_empty());
+}
+
+#[test]
+fn precompress_in_place_twice() {
+	let temp_dir = create_temp_dir("precompress_in_place_twice");
+	fs::write(temp_dir.join("app.js"), "console.log('hello');\n".repeat(100)).unwrap();
+	
+	let cache_bust = CacheBust::builder()
+		.in_dir(&temp_dir)
+		.in_place(true)
+		.hash_length(8)
+		.precompress(Compression::Gzip)
+		.build();
+	
+	cache_bust.hash_dir().unwrap();
+	let report = cache_bust.hash_dir().unwrap();
+	
+	assert_eq!(report.assets.len(), 1);
+	assert_eq!(report.written().count(), 0);
+	assert_eq!(report.assets[0].compressed.len(), 1);
+	assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 2);
+}
+
+#[test]
+fn invalid_compression_level() {
+	let err = CacheBust::builder()
+		.in_dir(assets_dir())
+		.in_place(true)
+		.precompress_with_level(Compression::Gzip, 10)
+		.try_build().unwrap_err();
+	
+	assert!(matches!(err, CacheBustBuilderError::InvalidCompressionLevel {compression: Compression::Gzip, level: 10}));
+}
@@ -3,7 +3,7 @@
 
 use std::{path::PathBuf, process};
 
-use cache_bust::CacheBust;
+use cache_bust::{CacheBust, Compression, ExcludePolicy, HashAlgorithm, HashEncoding, IntegrityAlgorithm, NameTemplate, SourceMapPolicy, INTEGRITY_VAR};
 use clap::{Parser, ValueEnum};
 
 #[derive(Parser, Debug)]
@@ -17,9 +17,91 @@ struct Args {
 	/// A single file to hash instead of the entire directory
 	#[arg(short, long)]
 	file: Option<PathBuf>,
-	/// Prints either the hash, the name of the hashed file, or its path to stdout. Only works when --file is given
+	/// Prints either the hash, the name of the hashed file, its path, or its Subresource Integrity string to stdout. Only works when --file is given
 	#[arg(short, long)]
 	print: Option<Print>,
+	/// The hash algorithm to use: sha256, sha384, sha512, blake3 or xxh3. Defaults to the value of CACHE_BUST_HASH_ALGORITHM or sha256
+	#[arg(short, long)]
+	algorithm: Option<HashAlgorithm>,
+	/// The encoding of the hash: hex, base32 or base64url. Defaults to the value of CACHE_BUST_HASH_ENCODING or hex
+	#[arg(short, long)]
+	encoding: Option<HashEncoding>,
+	/// The number of characters to truncate the hash to, at least 8. Defaults to the value of CACHE_BUST_HASH_LENGTH or the full hash
+	#[arg(short = 'l', long)]
+	hash_length: Option<usize>,
+	/// The template for hashed file names using the placeholders {stem}, {hash} and {ext}. Defaults to the value of CACHE_BUST_NAME_TEMPLATE or {stem}.{hash}.{ext}
+	#[arg(short, long)]
+	template: Option<NameTemplate>,
+	/// Comma-separated extensions consisting of multiple parts which the hash isn't inserted into, like min.js or js.map. Defaults to the value of CACHE_BUST_COMPOUND_EXTENSIONS or a list of common ones
+	#[arg(long, value_delimiter = ',')]
+	compound_extensions: Option<Vec<String>>,
+	/// Computes a Subresource Integrity string of each file using sha256, sha384 or sha512, which is written to the manifest. Defaults to the value of CACHE_BUST_INTEGRITY, or sha384 when printing the integrity
+	#[arg(long)]
+	integrity: Option<IntegrityAlgorithm>,
+	/// Writes a JSON manifest mapping the original paths of the files to their hashed paths. Only works when --file isn't given
+	#[arg(short, long)]
+	manifest: Option<PathBuf>,
+	/// Writes an import map mapping the URLs of JavaScript modules to their hashed URLs, as a <script type="importmap"> element if the path ends in .html, otherwise as JSON. Only works when --file isn't given
+	#[arg(long, value_name = "PATH")]
+	import_map: Option<PathBuf>,
+	/// The URL the out directory is served at, which the URLs in the import map start with. Defaults to /
+	#[arg(long, value_name = "URL", requires = "import_map")]
+	import_map_base: Option<String>,
+	/// Only hashes files whose path relative to the source directory matches one of these globs, e.g. "**/*.js". Can be given multiple times
+	#[arg(long, value_name = "GLOB")]
+	include: Vec<String>,
+	/// Skips files whose path relative to the source directory matches this glob, e.g. "**/.DS_Store". Can be given multiple times
+	#[arg(long, value_name = "GLOB")]
+	exclude: Vec<String>,
+	/// Copies files whose path relative to the source directory matches this glob without hashing them, e.g. "index.html". Can be given multiple times
+	#[arg(long, value_name = "GLOB")]
+	pass_through: Vec<String>,
+	/// Updates the out directory incrementally instead of clearing it, only copying new files and removing stale ones
+	#[arg(long)]
+	incremental: bool,
+	/// Rewrites url() and @import references in CSS files to the hashed names of the referenced files
+	#[arg(long)]
+	rewrite_css: bool,
+	/// Rewrites relative import specifiers and new URL(..., import.meta.url) references in JavaScript files to the hashed names of the referenced files
+	#[arg(long)]
+	rewrite_js: bool,
+	/// What happens to source maps referenced by a sourceMappingURL comment: separate hashes them like other files, rewrite points the comment and the map's file and sources fields to the hashed names, drop removes them
+	#[arg(long, value_name = "POLICY")]
+	source_maps: Option<SourceMaps>,
+	/// Keeps the names of files whose path relative to the source directory matches this glob, e.g. "**/*.html", but rewrites their references to hashed files. Can be given multiple times
+	#[arg(long, value_name = "GLOB")]
+	entry: Vec<String>,
+	/// Clears the out directory even if it isn't empty and wasn't created by cachebust
+	#[arg(long)]
+	force: bool,
+	/// Writes a precompressed variant of each hashed file next to it using gzip, brotli or zstd, optionally followed by a level, e.g. "brotli:9". Can be given multiple times
+	#[arg(long, value_name = "ALGORITHM[:LEVEL]", value_parser = parse_precompress)]
+	precompress: Vec<(Compression, Option<u32>)>,
+	/// Comma-separated extensions of the files to precompress. Defaults to common text formats like html, css and js
+	#[arg(long, value_delimiter = ',', requires = "precompress")]
+	precompress_extensions: Option<Vec<String>>,
+	/// The minimum size in bytes of the files to precompress. Defaults to 1024
+	#[arg(long, value_name = "BYTES", requires = "precompress")]
+	precompress_min_size: Option<u64>,
+	/// The minimum percentage a precompressed variant has to be smaller than the file to be written. Defaults to 10
+	#[arg(long, value_name = "PERCENT", requires = "precompress")]
+	precompress_min_savings: Option<u8>,
+}
+
+fn parse_precompress(value: &str) -> Result<(Compression, Option<u32>), String> {
+	let (compression, level) = match value.split_once(':') {
+		Some((compression, level)) => (compression, Some(level.parse().map_err(|_| format!("invalid level {level:?}"))?)),
+		None => (value, None),
+	};
+	
+	Ok((compression.parse().map_err(|err| format!("{err}"))?, level))
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum SourceMaps {
+	Separate,
+	Rewrite,
+	Drop,
 }
 
 #[derive(ValueEnum, Clone, Copy, Debug)]
@@ -27,6 +109,7 @@ enum Print {
 	Hash,
 	FileName,
 	FilePath,
+	Integrity,
 }
 
 fn main() {
@@ -37,11 +120,123 @@ fn main() {
 		process::exit(1);
 	}
 	
+	if args.manifest.is_some() && args.file.is_some() {
+		eprintln!("[cache_bust/error] Option --manifest can't be used in combination with --file");
+		process::exit(1);
+	}
+	
+	if args.import_map.is_some() && args.file.is_some() {
+		eprintln!("[cache_bust/error] Option --import-map can't be used in combination with --file");
+		process::exit(1);
+	}
+	
 	let mut builder = CacheBust::builder()
 		.in_dir(args.source)
 		.is_build_script(false)
 		.enable_logging(args.print.is_none());
 	
+	if let Some(algorithm) = args.algorithm {
+		builder = builder.hash_algorithm(algorithm);
+	}
+	
+	if let Some(integrity) = args.integrity {
+		builder = builder.integrity(integrity);
+	} else if matches!(args.print, Some(Print::Integrity)) && std::env::var_os(INTEGRITY_VAR).is_none() {
+		builder = builder.integrity(IntegrityAlgorithm::default());
+	}
+	
+	if let Some(encoding) = args.encoding {
+		builder = builder.hash_encoding(encoding);
+	}
+	
+	if let Some(hash_length) = args.hash_length {
+		builder = builder.hash_length(hash_length);
+	}
+	
+	if let Some(template) = args.template {
+		builder = builder.name_template(template);
+	}
+	
+	if let Some(extensions) = args.compound_extensions {
+		builder = builder.compound_extensions(extensions);
+	}
+	
+	if let Some(manifest) = args.manifest {
+		builder = builder.manifest(manifest);
+	}
+	
+	if let Some(import_map) = args.import_map {
+		builder = builder.import_map(import_map);
+	}
+	
+	if let Some(base) = args.import_map_base {
+		builder = builder.import_map_base(base);
+	}
+	
+	for pattern in args.include {
+		builder = builder.include(pattern);
+	}
+	
+	for pattern in args.exclude {
+		builder = builder.exclude(pattern);
+	}
+	
+	for pattern in args.pass_through {
+		builder = builder.exclude_with(pattern, ExcludePolicy::CopyUnhashed);
+	}
+	
+	if args.incremental {
+		if args.out.is_none() {
+			eprintln!("[cache_bust/error] Option --incremental can only be used in combination with --out");
+			process::exit(1);
+		}
+		
+		builder = builder.incremental(true);
+	}
+	
+	if args.rewrite_css {
+		builder = builder.rewrite_css(true);
+	}
+	
+	if args.rewrite_js {
+		builder = builder.rewrite_js(true);
+	}
+	
+	if let Some(source_maps) = args.source_maps {
+		builder = builder.source_maps(match source_maps {
+			SourceMaps::Separate => SourceMapPolicy::Separate,
+			SourceMaps::Rewrite => SourceMapPolicy::Rewrite,
+			SourceMaps::Drop => SourceMapPolicy::Drop,
+		});
+	}
+	
+	for pattern in args.entry {
+		builder = builder.entry(pattern);
+	}
+	
+	if args.force {
+		builder = builder.force(true);
+	}
+	
+	for (compression, level) in args.precompress {
+		builder = match level {
+			Some(level) => builder.precompress_with_level(compression, level),
+			None => builder.precompress(compression),
+		};
+	}
+	
+	if let Some(extensions) = args.precompress_extensions {
+		builder = builder.precompress_extensions(extensions);
+	}
+	
+	if let Some(min_size) = args.precompress_min_size {
+		builder = builder.precompress_min_size(min_size);
+	}
+	
+	if let Some(min_savings) = args.precompress_min_savings {
+		builder = builder.precompress_min_savings(min_savings);
+	}
+	
 	if let Some(out) = args.out {
 		builder = builder.out_dir(out);
 	} else {
@@ -58,39 +253,43 @@ fn main() {
 	
 	match args.file {
 		None => {
-			cache_bust.hash_dir().unwrap_or_else(|err| {
+			let report = cache_bust.hash_dir().unwrap_or_else(|err| {
 				eprintln!("[cache_bust/error] An error occured: {err}");
 				process::exit(1);
 			});
+			
+			if args.incremental {
+				println!(
+					"[cache_bust/info] {} written, {} unchanged, {} removed",
+					report.written().count(),
+					report.assets.len() - report.written().count(),
+					report.removed.len(),
+				);
+			}
 		},
 		Some(file) => {
-			let path = cache_bust.hash_file(&file).unwrap_or_else(|err| {
+			let asset = cache_bust.hash_file(&file).unwrap_or_else(|err| {
 				eprintln!("[cache_bust/error] An error occured: {err}");
 				process::exit(1);
 			});
 			
 			if let Some(print) = args.print {
 				let result = match print {
-					Print::FileName => path.file_name().expect("File should have a name").to_str().map(ToOwned::to_owned),
-					Print::FilePath => path.canonicalize().expect("Path should be correct").to_str().map(ToOwned::to_owned),
-					Print::Hash => {
-						let stripped_path = if file.extension().is_some() {
-							path.with_extension("")
-						} else {
-							path
-						};
-						
-						let hash = stripped_path.extension().expect("File should have its hash as an extension")
-							.to_str().map(ToOwned::to_owned).expect("Hash should be valid UTF-8");
-						
-						println!("{hash}");
-						process::exit(0);
+					Print::FileName => asset.hashed_name.to_str().map(ToOwned::to_owned),
+					Print::FilePath => asset.dest.canonicalize().expect("Path should be correct").to_str().map(ToOwned::to_owned),
+					Print::Hash => Some(asset.hash),
+					Print::Integrity => match asset.integrity {
+						Some(integrity) => Some(integrity),
+						None => {
+							eprintln!("[cache_bust/error] No integrity was computed, use --integrity to enable it");
+							process::exit(1);
+						},
 					},
 				};
 				
 				match result {
 					None => {
-						eprintln!("[cache_bust/error] File path is unprintable: {path:?}");
+						eprintln!("[cache_bust/error] File path is unprintable: {:?}", asset.dest);
 						process::exit(1);
 					},
 					Some(line) => {
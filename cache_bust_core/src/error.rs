@@ -0,0 +1,154 @@
+use std::{error::Error, fmt::{self, Display}, io, path::{Path, PathBuf}};
+
+/// The step during which a file operation failed
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Operation {
+	/// Reading a file to hash it
+	Hash,
+	/// Reading a file
+	Read,
+	/// Writing a file
+	Write,
+	/// Creating a directory
+	CreateDir,
+	/// Copying a file
+	Copy,
+	/// Renaming a file
+	Rename,
+	/// Removing a file
+	Remove,
+	/// Removing a directory
+	RemoveDir,
+	/// Compressing a file
+	Compress,
+}
+
+impl Display for Operation {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		use Operation::*;
+		f.write_str(match self {
+			Hash => "hash",
+			Read => "read",
+			Write => "write",
+			CreateDir => "create directory",
+			Copy => "copy",
+			Rename => "rename",
+			Remove => "remove",
+			RemoveDir => "remove directory",
+			Compress => "compress",
+		})
+	}
+}
+
+/// Error from hashing or moving files
+#[derive(Debug)]
+pub enum CacheBustError {
+	/// A file operation failed.
+	Io {
+		/// The step that failed
+		operation: Operation,
+		/// The file or directory the operation was performed on
+		path: PathBuf,
+		/// The destination of the operation, if it has one
+		dest: Option<PathBuf>,
+		/// The underlying error
+		source: io::Error,
+	},
+	/// Walking a directory failed.
+	WalkDir {
+		/// The path at which the error occurred, if known
+		path: Option<PathBuf>,
+		/// The underlying error
+		source: io::Error,
+	},
+	/// The options given to build a `CacheBust` are invalid.
+	Build(Box<dyn Error + Send + Sync>),
+	/// Files reference each other in a cycle, so none of them can be hashed before the others.
+	/// The files are listed in the order they reference each other, the last one referencing the first.
+	ReferenceCycle(Vec<PathBuf>),
+	/// `out_dir` isn't empty and doesn't contain the marker file written by `CacheBust`, so it wasn't
+	/// created by cache_bust and won't be cleared unless `force` is set.
+	OutDirNotOwned(PathBuf),
+	/// A file can't be verified because the [NameTemplate][crate::NameTemplate] only adds the hash to the
+	/// query string, so the name of the file on disk carries no hash. Contains the template and the file.
+	QueryOnlyTemplate {
+		/// The template the file was hashed with
+		template: String,
+		/// The file that was to be verified
+		path: PathBuf,
+	},
+}
+
+impl CacheBustError {
+	/// Creates a [CacheBustError::Io] for an operation without a destination.
+	pub fn io(operation: Operation, path: impl Into<PathBuf>, source: io::Error) -> Self {
+		CacheBustError::Io {
+			operation,
+			path: path.into(),
+			dest: None,
+			source,
+		}
+	}
+	
+	/// Creates a [CacheBustError::Io] for an operation with a destination.
+	pub fn io_with_dest(operation: Operation, path: impl Into<PathBuf>, dest: impl Into<PathBuf>, source: io::Error) -> Self {
+		CacheBustError::Io {
+			operation,
+			path: path.into(),
+			dest: Some(dest.into()),
+			source,
+		}
+	}
+	
+	/// Returns the path of the file the error occurred at, if known.
+	pub fn path(&self) -> Option<&Path> {
+		use CacheBustError::*;
+		match self {
+			Io {path, ..} => Some(path),
+			WalkDir {path, ..} => path.as_deref(),
+			Build(_) => None,
+			ReferenceCycle(paths) => paths.first().map(PathBuf::as_path),
+			OutDirNotOwned(path) => Some(path),
+			QueryOnlyTemplate {path, ..} => Some(path),
+		}
+	}
+}
+
+impl Display for CacheBustError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		use CacheBustError::*;
+		match self {
+			Io {operation, path, dest: Some(dest), source} => write!(f, "failed to {operation} {path:?} to {dest:?}: {source}"),
+			Io {operation, path, dest: None, source} => write!(f, "failed to {operation} {path:?}: {source}"),
+			WalkDir {path: Some(path), source} => write!(f, "failed to walk directory at {path:?}: {source}"),
+			WalkDir {path: None, source} => write!(f, "failed to walk directory: {source}"),
+			Build(err) => write!(f, "{err}"),
+			ReferenceCycle(paths) => {
+				f.write_str("files reference each other in a cycle: ")?;
+				
+				for path in paths {
+					write!(f, "{path:?} -> ")?;
+				}
+				
+				match paths.first() {
+					Some(first) => write!(f, "{first:?}"),
+					None => Ok(()),
+				}
+			},
+			OutDirNotOwned(out_dir) => write!(f, "refusing to clear {out_dir:?} because it wasn't created by cache_bust, set force to clear it anyway"),
+			QueryOnlyTemplate {template, path} => write!(f, "can't verify {path:?}: the name template {template:?} carries no hash in the file name"),
+		}
+	}
+}
+
+impl Error for CacheBustError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		use CacheBustError::*;
+		match self {
+			Io {source, ..} => Some(source),
+			WalkDir {source, ..} => Some(source),
+			Build(err) => Some(err.as_ref()),
+			ReferenceCycle(_) | OutDirNotOwned(_) | QueryOnlyTemplate {..} => None,
+		}
+	}
+}
@@ -0,0 +1,227 @@
+use std::{ffi::OsString, fmt, fs::File, io::{self, Read}, path::{Path, PathBuf}};
+
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::{parse_hashed_name_with, CacheBustError, HashAlgorithm, HashOptions, Operation};
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// The hashed name of a file
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct HashedName {
+	/// The encoded hash of the file's contents
+	pub hash: String,
+	/// The path of the hashed file relative to the directory of the original file.
+	/// Usually this is only a file name, but it can include directories
+	/// depending on the [NameTemplate][crate::NameTemplate].
+	pub path: PathBuf,
+	/// The full hashed name, including a query string if the
+	/// [NameTemplate][crate::NameTemplate] has one.
+	pub name: OsString,
+	/// The Subresource Integrity string of the file's contents, if an
+	/// [integrity algorithm][HashOptions::integrity] is set
+	pub integrity: Option<String>,
+}
+
+/// The hash of some contents along with their Subresource Integrity string
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ContentHash {
+	/// The encoded hash
+	pub hash: String,
+	/// The integrity string, if an [integrity algorithm][HashOptions::integrity] is set
+	pub integrity: Option<String>,
+}
+
+/// Streaming hasher which reads its input in chunks using a buffer that's
+/// reused between calls, so hashing many or large files doesn't require
+/// holding them in memory.
+pub struct Hasher {
+	options: HashOptions,
+	buffer: Box<[u8]>,
+}
+
+impl fmt::Debug for Hasher {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Hasher")
+			.field("options", &self.options)
+			.finish_non_exhaustive()
+	}
+}
+
+impl Default for Hasher {
+	fn default() -> Self {
+		Self::new(HashOptions::default())
+	}
+}
+
+impl Hasher {
+	/// Creates a new [Hasher] with the given [HashOptions].
+	pub fn new(options: HashOptions) -> Self {
+		Self {
+			options,
+			buffer: vec![0; BUFFER_SIZE].into_boxed_slice(),
+		}
+	}
+	
+	/// Returns the [HashOptions] used by this hasher.
+	pub fn options(&self) -> &HashOptions {
+		&self.options
+	}
+	
+	/// Hashes everything read from `reader` and returns the encoded hash.
+	pub fn hash_reader(&mut self, reader: impl Read) -> Result<String, io::Error> {
+		Ok(self.hash_reader_with_integrity(reader)?.hash)
+	}
+	
+	/// Hashes everything read from `reader` and returns the encoded hash as well as the integrity
+	/// string if an [integrity algorithm][HashOptions::integrity] is set. Both are computed while
+	/// reading the contents once.
+	pub fn hash_reader_with_integrity(&mut self, mut reader: impl Read) -> Result<ContentHash, io::Error> {
+		let algorithm = self.options.get_algorithm();
+		let integrity_algorithm = self.options.get_integrity();
+		let mut state = State::new(algorithm);
+		
+		// the digest of the name hash is reused if both use the same algorithm
+		let mut integrity_state = integrity_algorithm
+			.filter(|integrity| integrity.hash_algorithm() != algorithm)
+			.map(|integrity| State::new(integrity.hash_algorithm()));
+		
+		loop {
+			let len = match reader.read(&mut self.buffer) {
+				Ok(0) => break,
+				Ok(len) => len,
+				Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+				Err(err) => return Err(err),
+			};
+			
+			state.update(&self.buffer[..len]);
+			
+			if let Some(integrity_state) = &mut integrity_state {
+				integrity_state.update(&self.buffer[..len]);
+			}
+		}
+		
+		let digest = state.finalize();
+		
+		let integrity = integrity_algorithm.map(|integrity| match integrity_state {
+			Some(integrity_state) => integrity.format(&integrity_state.finalize()),
+			None => integrity.format(&digest),
+		});
+		
+		Ok(ContentHash {
+			hash: self.options.encode_hash(&digest),
+			integrity,
+		})
+	}
+	
+	/// Hashes the file at `path` and returns the encoded hash.
+	pub fn hash_file(&mut self, path: &Path) -> Result<String, CacheBustError> {
+		Ok(self.hash_file_with_integrity(path)?.hash)
+	}
+	
+	/// Hashes the file at `path` and returns the encoded hash as well as the integrity string
+	/// if an [integrity algorithm][HashOptions::integrity] is set.
+	pub fn hash_file_with_integrity(&mut self, path: &Path) -> Result<ContentHash, CacheBustError> {
+		File::open(path)
+			.and_then(|file| self.hash_reader_with_integrity(file))
+			.map_err(|err| CacheBustError::io(Operation::Hash, path, err))
+	}
+	
+	/// Hashes the file at `path` and returns its name with the hash added
+	/// according to the [NameTemplate][crate::NameTemplate].
+	pub fn hashed_file_name(&mut self, path: &Path) -> Result<OsString, CacheBustError> {
+		Ok(self.hash_name(path)?.name)
+	}
+	
+	/// Hashes the file at `path` and returns its hashed name as well as the hash itself.
+	pub fn hash_name(&mut self, path: &Path) -> Result<HashedName, CacheBustError> {
+		let content_hash = self.hash_file_with_integrity(path)?;
+		Ok(self.name_with_content_hash(path, content_hash))
+	}
+	
+	/// Checks whether the name of the file at `path` contains the hash of its contents.
+	/// 
+	/// Returns `false` if the name doesn't contain a hash and an error if the
+	/// [NameTemplate][crate::NameTemplate] only adds the hash to the query string.
+	pub fn verify(&mut self, path: &Path) -> Result<bool, CacheBustError> {
+		let template = self.options.get_name_template();
+		
+		if template.is_query_only() {
+			return Err(CacheBustError::QueryOnlyTemplate {
+				template: template.as_str().to_owned(),
+				path: path.to_owned(),
+			});
+		}
+		
+		let Some(parsed) = parse_hashed_name_with(path, &self.options) else {
+			return Ok(false);
+		};
+		
+		Ok(self.hash_file(path)? == parsed.hash)
+	}
+	
+	/// Adds an already computed `hash` to the name of the file at `path`.
+	pub fn name_with_hash(&self, path: &Path, hash: String) -> HashedName {
+		self.name_with_content_hash(path, ContentHash {
+			hash,
+			integrity: None,
+		})
+	}
+	
+	/// Adds an already computed [ContentHash] to the name of the file at `path`.
+	pub fn name_with_content_hash(&self, path: &Path, content_hash: ContentHash) -> HashedName {
+		let ContentHash {hash, integrity} = content_hash;
+		let template = self.options.get_name_template();
+		let (stem, extension) = self.options.split_file_name(path.file_name().unwrap_or_default());
+		
+		HashedName {
+			path: template.render_path(stem, &hash, extension),
+			name: template.render(stem, &hash, extension),
+			hash,
+			integrity,
+		}
+	}
+}
+
+enum State {
+	Sha256(Sha256),
+	Sha384(Sha384),
+	Sha512(Sha512),
+	Blake3(Box<blake3::Hasher>),
+	Xxh3(Box<Xxh3>),
+}
+
+impl State {
+	fn new(algorithm: HashAlgorithm) -> Self {
+		match algorithm {
+			HashAlgorithm::Sha256 => State::Sha256(Sha256::new()),
+			HashAlgorithm::Sha384 => State::Sha384(Sha384::new()),
+			HashAlgorithm::Sha512 => State::Sha512(Sha512::new()),
+			HashAlgorithm::Blake3 => State::Blake3(Box::default()),
+			HashAlgorithm::Xxh3 => State::Xxh3(Box::default()),
+		}
+	}
+	
+	fn update(&mut self, data: &[u8]) {
+		match self {
+			State::Sha256(hasher) => hasher.update(data),
+			State::Sha384(hasher) => hasher.update(data),
+			State::Sha512(hasher) => hasher.update(data),
+			State::Blake3(hasher) => {
+				hasher.update(data);
+			},
+			State::Xxh3(hasher) => hasher.update(data),
+		}
+	}
+	
+	fn finalize(self) -> Vec<u8> {
+		match self {
+			State::Sha256(hasher) => hasher.finalize().to_vec(),
+			State::Sha384(hasher) => hasher.finalize().to_vec(),
+			State::Sha512(hasher) => hasher.finalize().to_vec(),
+			State::Blake3(hasher) => hasher.finalize().as_bytes().to_vec(),
+			State::Xxh3(hasher) => hasher.digest128().to_be_bytes().to_vec(),
+		}
+	}
+}
@@ -0,0 +1,67 @@
+use std::{fmt::{self, Display}, str::FromStr};
+
+use crate::{ConfigError, HashAlgorithm};
+
+/// Algorithm used to compute [Subresource Integrity] strings, like `sha384-oqVu[...]`, for the
+/// `integrity` attribute of `<script>` and `<link>` elements.
+/// 
+/// [Subresource Integrity]: https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum IntegrityAlgorithm {
+	/// SHA-256
+	Sha256,
+	/// SHA-384, which is the most commonly used one
+	#[default]
+	Sha384,
+	/// SHA-512
+	Sha512,
+}
+
+impl IntegrityAlgorithm {
+	pub(crate) const NAMES: [&'static str; 3] = ["sha256", "sha384", "sha512"];
+	
+	/// Returns the name of the algorithm, which is also the prefix of the integrity strings.
+	pub fn name(self) -> &'static str {
+		use IntegrityAlgorithm::*;
+		match self {
+			Sha256 => "sha256",
+			Sha384 => "sha384",
+			Sha512 => "sha512",
+		}
+	}
+	
+	/// Returns the [HashAlgorithm] computing the digests of this algorithm.
+	pub fn hash_algorithm(self) -> HashAlgorithm {
+		use IntegrityAlgorithm::*;
+		match self {
+			Sha256 => HashAlgorithm::Sha256,
+			Sha384 => HashAlgorithm::Sha384,
+			Sha512 => HashAlgorithm::Sha512,
+		}
+	}
+	
+	/// Builds the integrity string from the raw `digest` produced by this algorithm.
+	pub fn format(self, digest: &[u8]) -> String {
+		format!("{}-{}", self.name(), data_encoding::BASE64.encode(digest))
+	}
+}
+
+impl Display for IntegrityAlgorithm {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+impl FromStr for IntegrityAlgorithm {
+	type Err = ConfigError;
+	
+	fn from_str(name: &str) -> Result<Self, Self::Err> {
+		use IntegrityAlgorithm::*;
+		match name.to_ascii_lowercase().replace('-', "").as_str() {
+			"sha256" => Ok(Sha256),
+			"sha384" => Ok(Sha384),
+			"sha512" => Ok(Sha512),
+			_ => Err(ConfigError::UnknownIntegrityAlgorithm(name.to_owned())),
+		}
+	}
+}
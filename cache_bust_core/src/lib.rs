@@ -6,30 +6,42 @@
 //! 
 //! [cache_bust]: https://crates.io/crates/cache_bust
 
-use std::{ffi::OsString, fs::File, io::{self, Read}, path::Path};
+use std::{ffi::OsString, io::{self, Read}, path::Path};
 
-use sha2::{digest::Output, Digest, Sha256};
+mod error;
+pub use error::*;
+mod options;
+pub use options::*;
+mod hasher;
+pub use hasher::*;
+mod integrity;
+pub use integrity::*;
+mod template;
+pub use template::*;
+mod parse;
+pub use parse::*;
 
 /// Hashes the file at `path` using SHA-256 and returns its name with
 /// the hash added before the extension.
-pub fn hashed_file_name(path: &Path) -> Result<OsString, io::Error> {
-	let file = File::open(path)?;
-	let hash = hex::encode(hash_file(file)?);
-	
-	let mut file_name = path.file_stem().unwrap_or_default().to_owned();
-	file_name.push(".");
-	file_name.push(hash);
-	
-	if let Some(extension) = path.extension() {
-		file_name.push(".");
-		file_name.push(extension);
-	}
-	
-	Ok(file_name)
+/// 
+/// Use [hashed_file_name_with] for other [HashOptions].
+pub fn hashed_file_name(path: &Path) -> Result<OsString, CacheBustError> {
+	hashed_file_name_with(path, &HashOptions::default())
 }
 
-fn hash_file(mut file: File) -> Result<Output<Sha256>, io::Error> {
-	let mut data = Vec::new();
-	file.read_to_end(&mut data)?;
-	Ok(Sha256::digest(data))
+/// Hashes the file at `path` using the given [HashOptions] and returns its
+/// name with the hash added according to their [NameTemplate].
+/// 
+/// Use a [Hasher] to hash multiple files with the same options.
+pub fn hashed_file_name_with(path: &Path, options: &HashOptions) -> Result<OsString, CacheBustError> {
+	Hasher::new(options.clone()).hashed_file_name(path)
+}
+
+/// Hashes everything read from `reader` using SHA-256 and returns the hash
+/// encoded as hex. This allows hashing generated content without writing
+/// it to a file first.
+/// 
+/// Use a [Hasher] to use different [HashOptions].
+pub fn hash_reader(reader: impl Read) -> Result<String, io::Error> {
+	Hasher::default().hash_reader(reader)
 }
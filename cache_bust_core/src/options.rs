@@ -0,0 +1,494 @@
+use std::{env, error::Error, ffi::OsStr, fmt::{self, Display}, path::Path, str::FromStr};
+
+use crate::{IntegrityAlgorithm, NameTemplate};
+
+/// Environment variable used to select the [HashAlgorithm].
+/// 
+/// Both the `asset!` macro and the build-time API read this variable, so setting it
+/// once (e.g. in `.cargo/config.toml`) keeps the hashes of both in sync.
+pub const HASH_ALGORITHM_VAR: &str = "CACHE_BUST_HASH_ALGORITHM";
+
+/// Environment variable used to select the [HashEncoding].
+pub const HASH_ENCODING_VAR: &str = "CACHE_BUST_HASH_ENCODING";
+
+/// Environment variable used to truncate the hash to a number of characters.
+pub const HASH_LENGTH_VAR: &str = "CACHE_BUST_HASH_LENGTH";
+
+/// Environment variable used to select the [NameTemplate].
+pub const NAME_TEMPLATE_VAR: &str = "CACHE_BUST_NAME_TEMPLATE";
+
+/// Environment variable used to set the compound extensions as a comma-separated list.
+pub const COMPOUND_EXTENSIONS_VAR: &str = "CACHE_BUST_COMPOUND_EXTENSIONS";
+
+/// Environment variable used to select the [IntegrityAlgorithm] of the integrity strings computed
+/// along with the hashes. No integrity strings are computed if it isn't set.
+pub const INTEGRITY_VAR: &str = "CACHE_BUST_INTEGRITY";
+
+/// All the environment variables read by [HashOptions::from_env].
+pub const ENV_VARS: [&str; 6] = [
+	HASH_ALGORITHM_VAR,
+	HASH_ENCODING_VAR,
+	HASH_LENGTH_VAR,
+	NAME_TEMPLATE_VAR,
+	COMPOUND_EXTENSIONS_VAR,
+	INTEGRITY_VAR,
+];
+
+/// The compound extensions recognized by default.
+pub const DEFAULT_COMPOUND_EXTENSIONS: [&str; 10] = [
+	"tar.gz",
+	"tar.bz2",
+	"tar.xz",
+	"min.js",
+	"min.css",
+	"js.map",
+	"css.map",
+	"min.js.map",
+	"min.css.map",
+	"d.ts",
+];
+
+/// The minimum number of characters a hash can be truncated to.
+pub const MIN_HASH_LENGTH: usize = 8;
+
+/// Error from an invalid hashing option
+#[derive(Debug)]
+pub enum ConfigError {
+	/// The given name doesn't correspond to any [HashAlgorithm].
+	UnknownAlgorithm(String),
+	/// The given name doesn't correspond to any [HashEncoding].
+	UnknownEncoding(String),
+	/// The given name doesn't correspond to any [IntegrityAlgorithm].
+	UnknownIntegrityAlgorithm(String),
+	/// The given hash length is not a number.
+	InvalidHashLength(String),
+	/// The given hash length is shorter than [MIN_HASH_LENGTH].
+	HashLengthTooShort(usize),
+	/// The given [NameTemplate] is invalid.
+	InvalidTemplate {
+		/// The template as given
+		template: String,
+		/// Why the template is invalid
+		reason: &'static str,
+	},
+	/// The environment variable contains an invalid value.
+	InvalidEnvVar {
+		/// The name of the environment variable
+		var: &'static str,
+		/// The error caused by its value
+		error: Box<ConfigError>,
+	},
+}
+
+impl Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		use ConfigError::*;
+		match self {
+			UnknownAlgorithm(name) => write!(f, "unknown hash algorithm {name:?}, expected one of {}", HashAlgorithm::NAMES.join(", ")),
+			UnknownEncoding(name) => write!(f, "unknown hash encoding {name:?}, expected one of {}", HashEncoding::NAMES.join(", ")),
+			UnknownIntegrityAlgorithm(name) => write!(f, "unknown integrity algorithm {name:?}, expected one of {}", IntegrityAlgorithm::NAMES.join(", ")),
+			InvalidHashLength(length) => write!(f, "invalid hash length {length:?}, expected a number"),
+			HashLengthTooShort(length) => write!(f, "hash length {length} is too short, it must be at least {MIN_HASH_LENGTH}"),
+			InvalidTemplate {template, reason} => write!(f, "invalid name template {template:?}: {reason}"),
+			InvalidEnvVar {var, error} => write!(f, "invalid value for {var}: {error}"),
+		}
+	}
+}
+
+impl Error for ConfigError {}
+
+/// Algorithm used to hash the contents of files
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum HashAlgorithm {
+	/// SHA-256
+	#[default]
+	Sha256,
+	/// SHA-384
+	Sha384,
+	/// SHA-512
+	Sha512,
+	/// BLAKE3 with a 256 bit output
+	Blake3,
+	/// The non-cryptographic 128 bit variant of XXH3.
+	/// Much faster than the others, but shouldn't be used where collisions could be provoked.
+	Xxh3,
+}
+
+impl HashAlgorithm {
+	const NAMES: [&'static str; 5] = ["sha256", "sha384", "sha512", "blake3", "xxh3"];
+	
+	/// Returns the name of the algorithm as accepted by [FromStr].
+	pub fn name(self) -> &'static str {
+		use HashAlgorithm::*;
+		match self {
+			Sha256 => "sha256",
+			Sha384 => "sha384",
+			Sha512 => "sha512",
+			Blake3 => "blake3",
+			Xxh3 => "xxh3",
+		}
+	}
+	
+	/// Returns the number of bytes of the hashes produced by the algorithm.
+	pub fn output_len(self) -> usize {
+		use HashAlgorithm::*;
+		match self {
+			Sha256 => 32,
+			Sha384 => 48,
+			Sha512 => 64,
+			Blake3 => 32,
+			Xxh3 => 16,
+		}
+	}
+}
+
+impl Display for HashAlgorithm {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+impl FromStr for HashAlgorithm {
+	type Err = ConfigError;
+	
+	fn from_str(name: &str) -> Result<Self, Self::Err> {
+		use HashAlgorithm::*;
+		match name.to_ascii_lowercase().replace('-', "").as_str() {
+			"sha256" => Ok(Sha256),
+			"sha384" => Ok(Sha384),
+			"sha512" => Ok(Sha512),
+			"blake3" => Ok(Blake3),
+			"xxh3" => Ok(Xxh3),
+			_ => Err(ConfigError::UnknownAlgorithm(name.to_owned())),
+		}
+	}
+}
+
+/// Encoding used to turn a hash into a string
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum HashEncoding {
+	/// Lowercase hexadecimal
+	#[default]
+	Hex,
+	/// Lowercase base32 ([RFC 4648]) without padding
+	/// 
+	/// [RFC 4648]: https://datatracker.ietf.org/doc/html/rfc4648#section-6
+	Base32,
+	/// URL-safe base64 ([RFC 4648]) without padding
+	/// 
+	/// [RFC 4648]: https://datatracker.ietf.org/doc/html/rfc4648#section-5
+	Base64Url,
+}
+
+impl HashEncoding {
+	const NAMES: [&'static str; 3] = ["hex", "base32", "base64url"];
+	
+	/// Returns the name of the encoding as accepted by [FromStr].
+	pub fn name(self) -> &'static str {
+		use HashEncoding::*;
+		match self {
+			Hex => "hex",
+			Base32 => "base32",
+			Base64Url => "base64url",
+		}
+	}
+	
+	/// Encodes the given bytes.
+	pub fn encode(self, bytes: &[u8]) -> String {
+		use HashEncoding::*;
+		match self {
+			Hex => data_encoding::HEXLOWER.encode(bytes),
+			Base32 => data_encoding::BASE32_NOPAD.encode(bytes).to_ascii_lowercase(),
+			Base64Url => data_encoding::BASE64URL_NOPAD.encode(bytes),
+		}
+	}
+	
+	/// Returns the number of characters `len` bytes are encoded to.
+	pub fn encoded_len(self, len: usize) -> usize {
+		use HashEncoding::*;
+		match self {
+			Hex => data_encoding::HEXLOWER.encode_len(len),
+			Base32 => data_encoding::BASE32_NOPAD.encode_len(len),
+			Base64Url => data_encoding::BASE64URL_NOPAD.encode_len(len),
+		}
+	}
+	
+	/// Returns whether `c` can be part of an encoded hash.
+	pub fn is_valid_char(self, c: char) -> bool {
+		use HashEncoding::*;
+		match self {
+			Hex => matches!(c, '0'..='9' | 'a'..='f'),
+			Base32 => matches!(c, 'a'..='z' | '2'..='7'),
+			Base64Url => matches!(c, 'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '_'),
+		}
+	}
+}
+
+impl Display for HashEncoding {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+impl FromStr for HashEncoding {
+	type Err = ConfigError;
+	
+	fn from_str(name: &str) -> Result<Self, Self::Err> {
+		use HashEncoding::*;
+		match name.to_ascii_lowercase().replace('-', "").as_str() {
+			"hex" => Ok(Hex),
+			"base32" => Ok(Base32),
+			"base64url" => Ok(Base64Url),
+			_ => Err(ConfigError::UnknownEncoding(name.to_owned())),
+		}
+	}
+}
+
+/// Options specifying how file names are hashed
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct HashOptions {
+	algorithm: HashAlgorithm,
+	encoding: HashEncoding,
+	length: Option<usize>,
+	template: NameTemplate,
+	compound_extensions: Vec<String>,
+	integrity: Option<IntegrityAlgorithm>,
+}
+
+impl Default for HashOptions {
+	fn default() -> Self {
+		Self {
+			algorithm: HashAlgorithm::default(),
+			encoding: HashEncoding::default(),
+			length: None,
+			template: NameTemplate::default(),
+			compound_extensions: DEFAULT_COMPOUND_EXTENSIONS.map(ToOwned::to_owned).to_vec(),
+			integrity: None,
+		}
+	}
+}
+
+impl HashOptions {
+	/// Reads the options from the `CACHE_BUST_*` environment variables, falling back to
+	/// the defaults for unset variables.
+	/// 
+	/// The variables are:
+	/// - `CACHE_BUST_HASH_ALGORITHM`: see [HashAlgorithm]
+	/// - `CACHE_BUST_HASH_ENCODING`: see [HashEncoding]
+	/// - `CACHE_BUST_HASH_LENGTH`: see [HashOptions::hash_length]
+	/// - `CACHE_BUST_NAME_TEMPLATE`: see [NameTemplate]
+	/// - `CACHE_BUST_COMPOUND_EXTENSIONS`: see [HashOptions::compound_extensions]
+	/// - `CACHE_BUST_INTEGRITY`: see [HashOptions::integrity]
+	pub fn from_env() -> Result<Self, ConfigError> {
+		let mut options = Self::default();
+		
+		if let Some(algorithm) = parse_env_var(HASH_ALGORITHM_VAR, str::parse)? {
+			options.algorithm = algorithm;
+		}
+		
+		if let Some(encoding) = parse_env_var(HASH_ENCODING_VAR, str::parse)? {
+			options.encoding = encoding;
+		}
+		
+		if let Some(length) = parse_env_var(HASH_LENGTH_VAR, parse_hash_length)? {
+			options = options.hash_length(length)
+				.map_err(|error| ConfigError::InvalidEnvVar {
+					var: HASH_LENGTH_VAR,
+					error: Box::new(error),
+				})?;
+		}
+		
+		if let Some(template) = parse_env_var(NAME_TEMPLATE_VAR, str::parse)? {
+			options.template = template;
+		}
+		
+		if let Some(extensions) = parse_env_var(COMPOUND_EXTENSIONS_VAR, |extensions| Ok(extensions.split(',').map(ToOwned::to_owned).collect::<Vec<_>>()))? {
+			options = options.compound_extensions(extensions);
+		}
+		
+		if let Some(integrity) = parse_env_var(INTEGRITY_VAR, str::parse)? {
+			options.integrity = Some(integrity);
+		}
+		
+		Ok(options)
+	}
+	
+	/// Specifies the algorithm used to hash files.
+	/// 
+	/// # Default
+	/// 
+	/// [HashAlgorithm::Sha256]
+	pub fn algorithm(mut self, algorithm: HashAlgorithm) -> Self {
+		self.algorithm = algorithm;
+		self
+	}
+	
+	/// Specifies the encoding used to turn the hash into a string.
+	/// 
+	/// # Default
+	/// 
+	/// [HashEncoding::Hex]
+	pub fn encoding(mut self, encoding: HashEncoding) -> Self {
+		self.encoding = encoding;
+		self
+	}
+	
+	/// Specifies the number of characters the encoded hash is truncated to.
+	/// If the encoded hash is shorter than `length` it is used as is.
+	/// 
+	/// # Default
+	/// 
+	/// The hash isn't truncated.
+	/// 
+	/// # Errors
+	/// 
+	/// Errors if `length` is shorter than [MIN_HASH_LENGTH].
+	pub fn hash_length(mut self, length: usize) -> Result<Self, ConfigError> {
+		if length < MIN_HASH_LENGTH {
+			return Err(ConfigError::HashLengthTooShort(length));
+		}
+		
+		self.length = Some(length);
+		Ok(self)
+	}
+	
+	/// Specifies the template used to add the hash to file names.
+	/// 
+	/// # Default
+	/// 
+	/// `{stem}.{hash}.{ext}`
+	pub fn name_template(mut self, template: NameTemplate) -> Self {
+		self.template = template;
+		self
+	}
+	
+	/// Specifies extensions consisting of multiple parts, like `min.js` or `js.map`,
+	/// which are kept together when adding the hash to a file name. This makes
+	/// `app.min.js` become `app.f04a[...].min.js` instead of `app.min.f04a[...].js`.
+	/// If multiple compound extensions match a file, the longest one is used. They are
+	/// matched case-insensitively, so `APP.MIN.JS` becomes `APP.f04a[...].MIN.JS`.
+	/// 
+	/// Leading dots and empty extensions are ignored.
+	/// 
+	/// # Default
+	/// 
+	/// [DEFAULT_COMPOUND_EXTENSIONS]
+	pub fn compound_extensions(mut self, extensions: impl IntoIterator<Item = impl Into<String>>) -> Self {
+		self.compound_extensions = extensions.into_iter()
+			.map(Into::into)
+			.map(|extension| extension.trim().trim_start_matches('.').to_owned())
+			.filter(|extension| !extension.is_empty())
+			.collect();
+		self
+	}
+	
+	/// Specifies the algorithm used to compute a [Subresource Integrity] string of each file while hashing it,
+	/// which doesn't require reading the file again.
+	/// 
+	/// [Subresource Integrity]: https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity
+	/// 
+	/// # Default
+	/// 
+	/// No integrity strings are computed.
+	pub fn integrity(mut self, algorithm: IntegrityAlgorithm) -> Self {
+		self.integrity = Some(algorithm);
+		self
+	}
+	
+	/// Returns the algorithm used to hash files.
+	pub fn get_algorithm(&self) -> HashAlgorithm {
+		self.algorithm
+	}
+	
+	/// Returns the encoding used to turn the hash into a string.
+	pub fn get_encoding(&self) -> HashEncoding {
+		self.encoding
+	}
+	
+	/// Returns the number of characters the hash is truncated to, if any.
+	pub fn get_hash_length(&self) -> Option<usize> {
+		self.length
+	}
+	
+	/// Returns the template used to add the hash to file names.
+	pub fn get_name_template(&self) -> &NameTemplate {
+		&self.template
+	}
+	
+	/// Returns the extensions consisting of multiple parts which are kept together.
+	pub fn get_compound_extensions(&self) -> &[String] {
+		&self.compound_extensions
+	}
+	
+	/// Returns the algorithm used to compute integrity strings, if any.
+	pub fn get_integrity(&self) -> Option<IntegrityAlgorithm> {
+		self.integrity
+	}
+	
+	/// Splits a file name into its stem and extension, taking compound extensions into account.
+	pub fn split_file_name<'a>(&self, file_name: &'a OsStr) -> (&'a OsStr, Option<&'a OsStr>) {
+		let compound = file_name.to_str().and_then(|file_name| {
+			self.compound_extensions.iter()
+				.filter_map(|extension| {
+					let start = file_name.len().checked_sub(extension.len())?;
+					let suffix = file_name.get(start..)?;
+					let stem = file_name[..start].strip_suffix('.').filter(|_| suffix.eq_ignore_ascii_case(extension))?;
+					(!stem.is_empty()).then(|| (stem, &file_name[stem.len() + 1..]))
+				})
+				.max_by_key(|(_, extension)| extension.len())
+		});
+		
+		match compound {
+			Some((stem, extension)) => (stem.as_ref(), Some(extension.as_ref())),
+			None => {
+				let path = Path::new(file_name);
+				(path.file_stem().unwrap_or_default(), path.extension())
+			},
+		}
+	}
+	
+	/// Returns the number of characters of the encoded hashes, taking the
+	/// hash length into account.
+	pub fn encoded_hash_len(&self) -> usize {
+		let len = self.encoding.encoded_len(self.algorithm.output_len());
+		
+		match self.length {
+			Some(length) => length.min(len),
+			None => len,
+		}
+	}
+	
+	/// Returns whether `hash` could be a hash produced with these options.
+	pub fn is_valid_hash(&self, hash: &str) -> bool {
+		hash.len() == self.encoded_hash_len() && hash.chars().all(|c| self.encoding.is_valid_char(c))
+	}
+	
+	/// Encodes the raw `digest` of a file and truncates it according to these options.
+	pub fn encode_hash(&self, digest: &[u8]) -> String {
+		let mut hash = self.encoding.encode(digest);
+		
+		if let Some(length) = self.length {
+			hash.truncate(length);
+		}
+		
+		hash
+	}
+}
+
+fn parse_hash_length(length: &str) -> Result<usize, ConfigError> {
+	length.trim().parse()
+		.map_err(|_| ConfigError::InvalidHashLength(length.to_owned()))
+}
+
+fn parse_env_var<T>(var: &'static str, parse: impl FnOnce(&str) -> Result<T, ConfigError>) -> Result<Option<T>, ConfigError> {
+	let Some(value) = env::var_os(var) else {
+		return Ok(None);
+	};
+	
+	parse(&value.to_string_lossy())
+		.map(Some)
+		.map_err(|error| ConfigError::InvalidEnvVar {
+			var,
+			error: Box::new(error),
+		})
+}
@@ -0,0 +1,81 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::{CacheBustError, HashAlgorithm, HashOptions, Hasher};
+
+/// A hashed file name split into the original name and the hash
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParsedName {
+	/// The name before the hash was added, including any leading
+	/// directories that weren't added by the [NameTemplate][crate::NameTemplate]
+	pub original: PathBuf,
+	/// The encoded hash
+	pub hash: String,
+	/// The algorithm the hash was produced with
+	pub algorithm: HashAlgorithm,
+}
+
+/// Parses a name produced by [hashed_file_name][crate::hashed_file_name] using the
+/// default [HashOptions] and returns the original name and the hash.
+/// 
+/// Returns `None` if `name` isn't a hashed name.
+pub fn parse_hashed_name(name: &Path) -> Option<ParsedName> {
+	parse_hashed_name_with(name, &HashOptions::default())
+}
+
+/// Parses a name produced with the given [HashOptions] and returns the original name and the hash.
+/// `name` can contain directories, e.g. `images/circle.f04a[...].png` is parsed as `images/circle.png`.
+/// 
+/// Only hashes matching the algorithm, encoding and length of `options` are recognized.
+/// Returns `None` if `name` isn't a hashed name.
+pub fn parse_hashed_name_with(name: &Path, options: &HashOptions) -> Option<ParsedName> {
+	let template = options.get_name_template();
+	let components: Vec<Component> = name.components().collect();
+	
+	// split off the directories which aren't part of the template, keeping a root or prefix out of the name
+	let first_normal = components.iter()
+		.position(|component| matches!(component, Component::Normal(_)))
+		.unwrap_or(components.len());
+	let split = components.len().saturating_sub(template.depth() + 1).max(first_normal);
+	let (prefix, name) = components.split_at(split);
+	
+	let name = name.iter()
+		.map(|component| component.as_os_str().to_str())
+		.collect::<Option<Vec<_>>>()?
+		.join("/");
+	
+	let encoding = options.get_encoding();
+	let name_match = template.match_name(&name, options.encoded_hash_len(), |c| encoding.is_valid_char(c))?;
+	
+	let mut file_name = name_match.stem.to_owned();
+	
+	if let Some(extension) = name_match.extension {
+		file_name.push('.');
+		file_name.push_str(extension);
+	}
+	
+	let mut original: PathBuf = prefix.iter().collect();
+	original.push(file_name);
+	
+	Some(ParsedName {
+		original,
+		hash: name_match.hash.to_owned(),
+		algorithm: options.get_algorithm(),
+	})
+}
+
+/// Checks whether the name of the file at `path` contains the hash of its
+/// contents using the default [HashOptions].
+/// 
+/// Returns `false` if the name doesn't contain a hash.
+pub fn verify_hashed_file(path: &Path) -> Result<bool, CacheBustError> {
+	verify_hashed_file_with(path, &HashOptions::default())
+}
+
+/// Checks whether the name of the file at `path` contains the hash of its
+/// contents using the given [HashOptions].
+/// 
+/// Returns `false` if the name doesn't contain a hash. Use a [Hasher]
+/// to verify multiple files with the same options.
+pub fn verify_hashed_file_with(path: &Path, options: &HashOptions) -> Result<bool, CacheBustError> {
+	Hasher::new(options.clone()).verify(path)
+}
@@ -0,0 +1,314 @@
+use std::{ffi::{OsStr, OsString}, fmt::{self, Display}, path::PathBuf, str::FromStr};
+
+use crate::ConfigError;
+
+/// Template describing where the hash is added to a file name.
+/// 
+/// Templates can contain the placeholders `{stem}`, `{hash}` and `{ext}`, e.g. the default
+/// `{stem}.{hash}.{ext}` turns `circle.png` into `circle.f04a[...].png`. Other examples are
+/// `{stem}-{hash}.{ext}`, `{hash}/{stem}.{ext}`, which puts the file in a directory named after
+/// its hash, or `{stem}.{ext}?v={hash}`, which keeps the file's name and only adds the hash
+/// as a query string to its URL.
+/// 
+/// If a file has no extension, `{ext}` is left out together with a `.` directly preceding it.
+/// 
+/// Templates are validated once when parsed:
+/// - `{hash}`, `{stem}` and `{ext}` have to occur exactly once
+/// - `{stem}` and `{ext}` can't be part of the query string
+/// - the path can't be absolute, contain empty segments or `.`/`..` segments
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct NameTemplate {
+	source: String,
+	path: Vec<Part>,
+	query: Option<Vec<Part>>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Part {
+	Literal(String),
+	Stem,
+	Hash,
+	Ext,
+}
+
+impl Default for NameTemplate {
+	fn default() -> Self {
+		Self::parse("{stem}.{hash}.{ext}").expect("default template should be valid")
+	}
+}
+
+impl NameTemplate {
+	/// Parses and validates a template.
+	pub fn parse(template: &str) -> Result<Self, ConfigError> {
+		let invalid = |reason| ConfigError::InvalidTemplate {
+			template: template.to_owned(),
+			reason,
+		};
+		
+		let (path, query) = match template.split_once('?') {
+			Some((path, query)) => (path, Some(query)),
+			None => (template, None),
+		};
+		
+		let path = parse_parts(path).ok_or_else(|| invalid("unknown placeholder or unmatched brace"))?;
+		let query = query.map(parse_parts)
+			.map(|query| query.ok_or_else(|| invalid("unknown placeholder or unmatched brace")))
+			.transpose()?;
+		
+		let count = |part: &Part| {
+			path.iter().chain(query.iter().flatten())
+				.filter(|other| *other == part)
+				.count()
+		};
+		
+		if count(&Part::Hash) != 1 {
+			return Err(invalid("{hash} has to occur exactly once"));
+		}
+		
+		if count(&Part::Stem) != 1 {
+			return Err(invalid("{stem} has to occur exactly once"));
+		}
+		
+		// without {ext} the extension would silently be dropped
+		if count(&Part::Ext) != 1 {
+			return Err(invalid("{ext} has to occur exactly once"));
+		}
+		
+		if query.iter().flatten().any(|part| matches!(part, Part::Stem | Part::Ext)) {
+			return Err(invalid("{stem} and {ext} can't be part of the query string"));
+		}
+		
+		let literal_path: String = path.iter()
+			.map(|part| match part {
+				Part::Literal(literal) => literal.as_str(),
+				_ => "_",
+			})
+			.collect();
+		
+		if literal_path.starts_with('/') || literal_path.contains('\\') {
+			return Err(invalid("the path has to be relative"));
+		}
+		
+		if literal_path.split('/').any(|segment| segment.is_empty() || segment == "." || segment == "..") {
+			return Err(invalid("the path can't contain empty, `.` or `..` segments"));
+		}
+		
+		Ok(Self {
+			source: template.to_owned(),
+			path,
+			query,
+		})
+	}
+	
+	/// Returns the template as it was parsed.
+	pub fn as_str(&self) -> &str {
+		&self.source
+	}
+	
+	/// Returns whether the hash is only part of the query string, meaning the
+	/// file itself keeps its name.
+	pub fn is_query_only(&self) -> bool {
+		!self.path.contains(&Part::Hash)
+	}
+	
+	/// Returns the number of directories the template adds to the path of a file,
+	/// e.g. 1 for `{hash}/{stem}.{ext}`.
+	pub fn depth(&self) -> usize {
+		self.path.iter()
+			.map(|part| match part {
+				Part::Literal(literal) => literal.matches('/').count(),
+				_ => 0,
+			})
+			.sum()
+	}
+	
+	/// Renders the path of the hashed file, relative to the directory of the original file.
+	/// This excludes the query string.
+	pub fn render_path(&self, stem: &OsStr, hash: &str, extension: Option<&OsStr>) -> PathBuf {
+		render_parts(&self.path, stem, hash, extension).into()
+	}
+	
+	/// Renders the full name of the hashed file, including the query string.
+	pub fn render(&self, stem: &OsStr, hash: &str, extension: Option<&OsStr>) -> OsString {
+		let mut name = render_parts(&self.path, stem, hash, extension);
+		
+		if let Some(query) = &self.query {
+			name.push("?");
+			name.push(render_parts(query, stem, hash, extension));
+		}
+		
+		name
+	}
+}
+
+/// The parts of a name matched by [NameTemplate::match_name]
+pub(crate) struct NameMatch<'a> {
+	pub stem: &'a str,
+	pub hash: &'a str,
+	pub extension: Option<&'a str>,
+}
+
+impl NameTemplate {
+	/// Matches a name produced by [NameTemplate::render] against the template, where
+	/// `name` contains as many directories as the template adds, but no others.
+	pub(crate) fn match_name<'a>(&self, name: &'a str, hash_len: usize, is_hash_char: impl Fn(char) -> bool) -> Option<NameMatch<'a>> {
+		let (path, query) = match name.split_once('?') {
+			Some((path, query)) => (path, Some(query)),
+			None => (name, None),
+		};
+		
+		let matcher = Matcher {
+			hash_len,
+			is_hash_char: &is_hash_char,
+		};
+		
+		let mut captures = NameMatch {
+			stem: "",
+			hash: "",
+			extension: None,
+		};
+		
+		let matched_path = matcher.match_parts(&self.path, path, &mut captures) || {
+			captures.extension = None;
+			matcher.match_parts(&without_extension(&self.path), path, &mut captures)
+		};
+		
+		let matched_query = match (&self.query, query) {
+			(None, None) => true,
+			(Some(parts), Some(query)) => matcher.match_parts(parts, query, &mut captures),
+			_ => false,
+		};
+		
+		(matched_path && matched_query).then_some(captures)
+	}
+}
+
+struct Matcher<'f> {
+	hash_len: usize,
+	is_hash_char: &'f dyn Fn(char) -> bool,
+}
+
+impl Matcher<'_> {
+	fn match_parts<'a>(&self, parts: &[Part], input: &'a str, captures: &mut NameMatch<'a>) -> bool {
+		let Some((part, rest)) = parts.split_first() else {
+			return input.is_empty();
+		};
+		
+		match part {
+			Part::Literal(literal) => input.strip_prefix(literal.as_str())
+				.is_some_and(|input| self.match_parts(rest, input, captures)),
+			Part::Hash => {
+				let Some(hash) = input.get(..self.hash_len) else {
+					return false;
+				};
+				
+				if !hash.chars().all(self.is_hash_char) {
+					return false;
+				}
+				
+				captures.hash = hash;
+				self.match_parts(rest, &input[self.hash_len..], captures)
+			},
+			Part::Stem | Part::Ext => {
+				for end in (1..=input.len()).filter(|end| input.is_char_boundary(*end)) {
+					let segment = &input[..end];
+					
+					if segment.ends_with('/') {
+						break;
+					}
+					
+					if *part == Part::Stem {
+						captures.stem = segment;
+					} else {
+						captures.extension = Some(segment);
+					}
+					
+					if self.match_parts(rest, &input[end..], captures) {
+						return true;
+					}
+				}
+				
+				false
+			},
+		}
+	}
+}
+
+fn without_extension(parts: &[Part]) -> Vec<Part> {
+	let mut parts = parts.to_vec();
+	
+	if let Some(index) = parts.iter().position(|part| *part == Part::Ext) {
+		parts.remove(index);
+		
+		if let Some(Part::Literal(literal)) = index.checked_sub(1).map(|index| &mut parts[index]) {
+			if literal.ends_with('.') {
+				literal.pop();
+			}
+		}
+	}
+	
+	parts
+}
+
+impl Display for NameTemplate {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.source)
+	}
+}
+
+impl FromStr for NameTemplate {
+	type Err = ConfigError;
+	
+	fn from_str(template: &str) -> Result<Self, Self::Err> {
+		Self::parse(template)
+	}
+}
+
+fn parse_parts(mut template: &str) -> Option<Vec<Part>> {
+	let mut parts = Vec::new();
+	
+	while !template.is_empty() {
+		let Some(start) = template.find(['{', '}']) else {
+			parts.push(Part::Literal(template.to_owned()));
+			break;
+		};
+		
+		if start > 0 {
+			parts.push(Part::Literal(template[..start].to_owned()));
+		}
+		
+		let end = template[start..].find('}')? + start;
+		
+		parts.push(match &template[start..=end] {
+			"{stem}" => Part::Stem,
+			"{hash}" => Part::Hash,
+			"{ext}" => Part::Ext,
+			_ => return None,
+		});
+		
+		template = &template[end + 1..];
+	}
+	
+	Some(parts)
+}
+
+fn render_parts(parts: &[Part], stem: &OsStr, hash: &str, extension: Option<&OsStr>) -> OsString {
+	let mut rendered = OsString::new();
+	
+	for (i, part) in parts.iter().enumerate() {
+		match part {
+			Part::Literal(literal) => {
+				let omit_dot = extension.is_none() && parts.get(i + 1) == Some(&Part::Ext);
+				rendered.push(match literal.strip_suffix('.') {
+					Some(literal) if omit_dot => literal,
+					_ => literal,
+				});
+			},
+			Part::Stem => rendered.push(stem),
+			Part::Hash => rendered.push(hash),
+			Part::Ext => rendered.push(extension.unwrap_or_default()),
+		}
+	}
+	
+	rendered
+}
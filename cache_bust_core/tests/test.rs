@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{ffi::OsString, fs, io::{self, Read}, path::{Path, PathBuf}};
 
 use cache_bust_core::*;
 
@@ -21,3 +21,252 @@ fn test_hello() {
 	
 	assert_eq!(hashed_name, "hello.97f24948156c5ea491bda3d05d12b334c57409e3b746e73215585b2fe99fb098");
 }
+
+fn hello_txt_with(options: HashOptions) -> OsString {
+	let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+	path.push("tests");
+	path.push("hello.txt");
+	hashed_file_name_with(&path, &options).unwrap()
+}
+
+#[test]
+fn test_algorithms() {
+	let hashed_name = |algorithm| hello_txt_with(HashOptions::default().algorithm(algorithm));
+	
+	assert_eq!(hashed_name(HashAlgorithm::Sha256), "hello.d9014c4624844aa5bac314773d6b689ad467fa4e1d1a50a1b8a99d5a95f72ff5.txt");
+	assert_eq!(hashed_name(HashAlgorithm::Sha384), "hello.79a7aec70847c242102b889b298a0720803b340b350cd9e89f574c684d46bfb232b92d2df356fd77e4d2047c43b3f8a0.txt");
+	assert_eq!(hashed_name(HashAlgorithm::Sha512), "hello.09e1e2a84c92b56c8280f4a1203c7cffd61b162cfe987278d4d6be9afbf38c0e8934cdadf83751f4e99d111352bffefc958e5a4852c8a7a29c95742ce59288a8.txt");
+	assert_eq!(hashed_name(HashAlgorithm::Blake3), "hello.94f1675bac4f8bc3c593c63dbf5fe78a0bfda01082af85d5b41a65096db56bff.txt");
+	assert_eq!(hashed_name(HashAlgorithm::Xxh3), "hello.c3082335d9e644a032c1b16a5ed98ffb.txt");
+}
+
+#[test]
+fn test_parse_algorithm() {
+	assert_eq!("sha256".parse::<HashAlgorithm>().unwrap(), HashAlgorithm::Sha256);
+	assert_eq!("SHA-384".parse::<HashAlgorithm>().unwrap(), HashAlgorithm::Sha384);
+	assert_eq!("blake3".parse::<HashAlgorithm>().unwrap(), HashAlgorithm::Blake3);
+	assert!("md5".parse::<HashAlgorithm>().is_err());
+	
+	for algorithm in [HashAlgorithm::Sha256, HashAlgorithm::Sha384, HashAlgorithm::Sha512, HashAlgorithm::Blake3, HashAlgorithm::Xxh3] {
+		assert_eq!(algorithm.to_string().parse::<HashAlgorithm>().unwrap(), algorithm);
+	}
+}
+
+#[test]
+fn test_encodings() {
+	let hashed_name = |encoding| hello_txt_with(HashOptions::default().encoding(encoding));
+	
+	assert_eq!(hashed_name(HashEncoding::Hex), "hello.d9014c4624844aa5bac314773d6b689ad467fa4e1d1a50a1b8a99d5a95f72ff5.txt");
+	assert_eq!(hashed_name(HashEncoding::Base32), "hello.3eauyrreqrfklowdcr3t223itlkgp6sodunfbinyvgovvfpxf72q.txt");
+	assert_eq!(hashed_name(HashEncoding::Base64Url), "hello.2QFMRiSESqW6wxR3PWtomtRn-k4dGlChuKmdWpX3L_U.txt");
+}
+
+#[test]
+fn test_hash_length() {
+	let options = HashOptions::default().hash_length(12).unwrap();
+	assert_eq!(hello_txt_with(options), "hello.d9014c462484.txt");
+	
+	let options = HashOptions::default().algorithm(HashAlgorithm::Xxh3).hash_length(64).unwrap();
+	assert_eq!(hello_txt_with(options), "hello.c3082335d9e644a032c1b16a5ed98ffb.txt");
+	
+	assert!(matches!(HashOptions::default().hash_length(4), Err(ConfigError::HashLengthTooShort(4))));
+}
+
+#[test]
+fn test_hash_reader() {
+	assert_eq!(hash_reader(&b"Hello, world!\n"[..]).unwrap(), "d9014c4624844aa5bac314773d6b689ad467fa4e1d1a50a1b8a99d5a95f72ff5");
+	assert_eq!(hash_reader(io::empty()).unwrap(), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+}
+
+#[test]
+fn test_integrity() {
+	let content_hash = |options: HashOptions| Hasher::new(options).hash_reader_with_integrity(&b"Hello, world!\n"[..]).unwrap();
+	
+	// the digest of the name hash is reused
+	let sha256 = content_hash(HashOptions::default().integrity(IntegrityAlgorithm::Sha256));
+	assert_eq!(sha256.hash, "d9014c4624844aa5bac314773d6b689ad467fa4e1d1a50a1b8a99d5a95f72ff5");
+	assert_eq!(sha256.integrity.unwrap(), "sha256-2QFMRiSESqW6wxR3PWtomtRn+k4dGlChuKmdWpX3L/U=");
+	
+	let sha384 = content_hash(HashOptions::default().integrity(IntegrityAlgorithm::Sha384));
+	assert_eq!(sha384.hash, "d9014c4624844aa5bac314773d6b689ad467fa4e1d1a50a1b8a99d5a95f72ff5");
+	assert_eq!(sha384.integrity.unwrap(), "sha384-eaeuxwhHwkIQK4ibKYoHIIA7NAs1DNnon1dMaE1Gv7IyuS0t81b9d+TSBHxDs/ig");
+	
+	let sha512 = content_hash(HashOptions::default().algorithm(HashAlgorithm::Blake3).integrity(IntegrityAlgorithm::Sha512));
+	assert_eq!(sha512.hash, "94f1675bac4f8bc3c593c63dbf5fe78a0bfda01082af85d5b41a65096db56bff");
+	assert_eq!(sha512.integrity.unwrap(), "sha512-CeHiqEyStWyCgPShIDx8/9YbFiz+mHJ41Na+mvvzjA6JNM2t+DdR9OmdERNSv/78lY5aSFLIp6KclXQs5ZKIqA==");
+	
+	assert_eq!(content_hash(HashOptions::default()).integrity, None);
+	
+	assert_eq!("SHA-512".parse::<IntegrityAlgorithm>().unwrap(), IntegrityAlgorithm::Sha512);
+	assert!(matches!("blake3".parse::<IntegrityAlgorithm>(), Err(ConfigError::UnknownIntegrityAlgorithm(_))));
+}
+
+#[test]
+fn test_hasher_large_input() {
+	// larger than the internal buffer to hash it in multiple chunks
+	let input = || io::repeat(b'a').take(1_000_000);
+	
+	let mut hasher = Hasher::default();
+	assert_eq!(hasher.hash_reader(input()).unwrap(), "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0");
+	// reusing the hasher gives the same result
+	assert_eq!(hasher.hash_reader(input()).unwrap(), "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0");
+}
+
+#[test]
+fn test_name_templates() {
+	let hashed_name = |template: &str| hello_txt_with(HashOptions::default()
+		.hash_length(8).unwrap()
+		.name_template(template.parse().unwrap())
+	);
+	
+	assert_eq!(hashed_name("{stem}.{hash}.{ext}"), "hello.d9014c46.txt");
+	assert_eq!(hashed_name("{stem}-{hash}.{ext}"), "hello-d9014c46.txt");
+	assert_eq!(hashed_name("{hash}/{stem}.{ext}"), "d9014c46/hello.txt");
+	assert_eq!(hashed_name("{stem}.{ext}?v={hash}"), "hello.txt?v=d9014c46");
+}
+
+#[test]
+fn test_name_template_without_extension() {
+	let template = NameTemplate::parse("{stem}-{hash}.{ext}").unwrap();
+	assert_eq!(template.render("hello".as_ref(), "1234", None), "hello-1234");
+	assert_eq!(template.render("hello".as_ref(), "1234", Some("txt".as_ref())), "hello-1234.txt");
+	
+	let template = NameTemplate::parse("{stem}.{ext}?v={hash}").unwrap();
+	assert_eq!(template.render("hello".as_ref(), "1234", None), "hello?v=1234");
+	assert_eq!(template.render_path("hello".as_ref(), "1234", None), PathBuf::from("hello"));
+	assert!(template.is_query_only());
+}
+
+#[test]
+fn test_invalid_name_templates() {
+	for template in [
+		"{stem}.{ext}",
+		"{hash}.{ext}",
+		"{stem}.{hash}.{hash}",
+		"{stem}-{hash}",
+		"{hash}/{stem}",
+		"{stem}.{ext}.{ext}?v={hash}",
+		"{stem}.{hash}.{extension}",
+		"{stem}.{hash",
+		"{stem}}.{hash}",
+		"/{hash}/{stem}.{ext}",
+		"{hash}//{stem}.{ext}",
+		"../{stem}.{hash}.{ext}",
+		"{hash}/?v={stem}",
+	] {
+		assert!(matches!(NameTemplate::parse(template), Err(ConfigError::InvalidTemplate {..})), "{template}");
+	}
+}
+
+#[test]
+fn test_compound_extensions() {
+	fn split_with<'a>(options: &HashOptions, file_name: &'a str) -> (&'a str, Option<&'a str>) {
+		let (stem, extension) = options.split_file_name(file_name.as_ref());
+		(stem.to_str().unwrap(), extension.map(|extension| extension.to_str().unwrap()))
+	}
+	
+	let options = HashOptions::default();
+	let split = |file_name| split_with(&options, file_name);
+	
+	assert_eq!(split("app.min.js"), ("app", Some("min.js")));
+	assert_eq!(split("bundle.js.map"), ("bundle", Some("js.map")));
+	assert_eq!(split("app.min.js.map"), ("app", Some("min.js.map")));
+	assert_eq!(split("types.d.ts"), ("types", Some("d.ts")));
+	assert_eq!(split("archive.tar.gz"), ("archive", Some("tar.gz")));
+	assert_eq!(split("app.js"), ("app", Some("js")));
+	assert_eq!(split("my.app.js"), ("my.app", Some("js")));
+	assert_eq!(split(".js.map"), (".js", Some("map")));
+	assert_eq!(split("hello"), ("hello", None));
+	assert_eq!(split("APP.MIN.JS"), ("APP", Some("MIN.JS")));
+	assert_eq!(split("bundle.JS.Map"), ("bundle", Some("JS.Map")));
+	
+	let options = HashOptions::default().compound_extensions([".min.js"]);
+	assert_eq!(split_with(&options, "bundle.js.map"), ("bundle.js", Some("map")));
+	assert_eq!(split_with(&options, "app.min.js"), ("app", Some("min.js")));
+}
+
+#[test]
+fn test_parse_hashed_name() {
+	let parsed = parse_hashed_name(Path::new("images/circle.f04a632bf7de8a58d730988671a9139d6f7b3b197bbc78b6c74a4542eaa4878d.png")).unwrap();
+	assert_eq!(parsed.original, Path::new("images/circle.png"));
+	assert_eq!(parsed.hash, "f04a632bf7de8a58d730988671a9139d6f7b3b197bbc78b6c74a4542eaa4878d");
+	assert_eq!(parsed.algorithm, HashAlgorithm::Sha256);
+	
+	let parsed = parse_hashed_name(Path::new("hello.97f24948156c5ea491bda3d05d12b334c57409e3b746e73215585b2fe99fb098")).unwrap();
+	assert_eq!(parsed.original, Path::new("hello"));
+	
+	let parsed = parse_hashed_name(Path::new("app.e4f99453fffe7c06cfa40d01d83263562f85ec8ce357dc7dc0e8ce70796c1a7b.min.js")).unwrap();
+	assert_eq!(parsed.original, Path::new("app.min.js"));
+	
+	assert_eq!(parse_hashed_name(Path::new("hello.txt")), None);
+	assert_eq!(parse_hashed_name(Path::new("hello.d9014c46.txt")), None);
+	assert_eq!(parse_hashed_name(Path::new("hello.D9014C4624844AA5BAC314773D6B689AD467FA4E1D1A50A1B8A99D5A95F72FF5.txt")), None);
+}
+
+#[test]
+fn test_parse_hashed_name_with_template() {
+	let parse = |template: &str, name: &str| {
+		let options = HashOptions::default()
+			.hash_length(8).unwrap()
+			.name_template(template.parse().unwrap());
+		parse_hashed_name_with(Path::new(name), &options)
+			.map(|parsed| (parsed.original.into_os_string().into_string().unwrap(), parsed.hash))
+	};
+	
+	assert_eq!(parse("{stem}-{hash}.{ext}", "a/hello-d9014c46.txt"), Some(("a/hello.txt".to_owned(), "d9014c46".to_owned())));
+	assert_eq!(parse("{hash}/{stem}.{ext}", "a/d9014c46/hello.txt"), Some(("a/hello.txt".to_owned(), "d9014c46".to_owned())));
+	assert_eq!(parse("{stem}.{ext}?v={hash}", "a/hello.txt?v=d9014c46"), Some(("a/hello.txt".to_owned(), "d9014c46".to_owned())));
+	assert_eq!(parse("{stem}.{ext}?v={hash}", "hello?v=d9014c46"), Some(("hello".to_owned(), "d9014c46".to_owned())));
+	assert_eq!(parse("{hash}/{stem}.{ext}", "a/hello.txt"), None);
+	assert_eq!(parse("{stem}.{ext}?v={hash}", "hello.txt"), None);
+	assert_eq!(parse("{hash}/{stem}.{ext}", "/d9014c46/hello.txt"), Some(("/hello.txt".to_owned(), "d9014c46".to_owned())));
+	assert_eq!(parse("{hash}/{stem}.{ext}", "/hello.txt"), None);
+}
+
+#[test]
+fn test_verify_hashed_file() {
+	let mut dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+	dir.push("test_verify_hashed_file");
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir(&dir).unwrap();
+	
+	let valid = dir.join("hello.d9014c4624844aa5bac314773d6b689ad467fa4e1d1a50a1b8a99d5a95f72ff5.txt");
+	fs::write(&valid, "Hello, world!\n").unwrap();
+	assert!(verify_hashed_file(&valid).unwrap());
+	
+	let invalid = dir.join("hello.0000000000000000000000000000000000000000000000000000000000000000.txt");
+	fs::write(&invalid, "Hello, world!\n").unwrap();
+	assert!(!verify_hashed_file(&invalid).unwrap());
+	
+	let unhashed = dir.join("hello.txt");
+	fs::write(&unhashed, "Hello, world!\n").unwrap();
+	assert!(!verify_hashed_file(&unhashed).unwrap());
+	
+	// the file on disk carries no hash with a query-only template
+	let options = HashOptions::default().name_template("{stem}.{ext}?v={hash}".parse().unwrap());
+	assert!(matches!(verify_hashed_file_with(&unhashed, &options), Err(CacheBustError::QueryOnlyTemplate {..})));
+	
+	let options = HashOptions::default().name_template("{hash}/{stem}.{ext}".parse().unwrap()).hash_length(8).unwrap();
+	let nested = dir.join("d9014c46").join("hello.txt");
+	fs::create_dir(nested.parent().unwrap()).unwrap();
+	fs::write(&nested, "Hello, world!\n").unwrap();
+	assert!(verify_hashed_file_with(&nested, &options).unwrap());
+}
+
+#[test]
+fn test_missing_file_error() {
+	let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+	path.push("tests");
+	path.push("missing.txt");
+	
+	let err = hashed_file_name(&path).unwrap_err();
+	
+	let CacheBustError::Io {operation, path: err_path, dest, source} = &err else {
+		panic!("unexpected error: {err:?}");
+	};
+	assert_eq!(*operation, Operation::Hash);
+	assert_eq!(err_path, &path);
+	assert_eq!(dest, &None);
+	assert_eq!(source.kind(), io::ErrorKind::NotFound);
+	assert!(err.to_string().starts_with(&format!("failed to hash {path:?}")));
+}
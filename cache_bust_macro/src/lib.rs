@@ -8,7 +8,7 @@
 
 use std::{env, path::PathBuf, str::FromStr};
 
-use cache_bust_core::hashed_file_name;
+use cache_bust_core::{hashed_file_name_with, HashOptions, Hasher, IntegrityAlgorithm};
 use litrs::StringLit;
 use proc_macro::{Literal, TokenStream, TokenTree};
 
@@ -28,6 +28,19 @@ use proc_macro::{Literal, TokenStream, TokenTree};
 /// while still erroring if the file doesn't exist. This can be useful if hashing is
 /// only wanted in some builds but not others.
 /// 
+/// The hash algorithm can be chosen with the `CACHE_BUST_HASH_ALGORITHM` environment
+/// variable (`sha256`, `sha384`, `sha512`, `blake3` or `xxh3`, defaulting to `sha256`).
+/// The hash can be shortened by setting `CACHE_BUST_HASH_LENGTH` to the number of characters
+/// to keep and its encoding changed by setting `CACHE_BUST_HASH_ENCODING` to `hex`, `base32`
+/// or `base64url`. Where the hash is added to the name is determined by `CACHE_BUST_NAME_TEMPLATE`,
+/// e.g. `{stem}-{hash}.{ext}` or `{stem}.{ext}?v={hash}`, defaulting to `{stem}.{hash}.{ext}`.
+/// Extensions consisting of multiple parts, like `min.js` or `js.map`, are kept together, so
+/// `app.min.js` becomes `app.f04a[...].min.js`. The recognized ones can be set as a comma-separated
+/// list in `CACHE_BUST_COMPOUND_EXTENSIONS`.
+/// These variables are read by `CacheBust` at build time as well, so both produce the same names.
+/// This doesn't hold for files whose contents `CacheBust` rewrites, like stylesheets with
+/// `rewrite_css` set, which have to be looked up through its manifest or lookup module.
+/// 
 /// # Examples
 /// 
 /// ```
@@ -45,29 +58,11 @@ use proc_macro::{Literal, TokenStream, TokenTree};
 /// ```
 #[proc_macro]
 pub fn asset(token_stream: TokenStream) -> TokenStream {
-	let mut iter = token_stream.into_iter();
-	let token = iter.next().expect("Expected file name as a string");
-	
-	if iter.next().is_some() {
-		panic!("Expected file name as a string");
-	}
-	
-	let literal = StringLit::try_from(token).expect("Expected file name as a string");
-	
-	let (local_path, is_absolute) = if literal.value().starts_with('/') {
-		(&literal.value()[1..], true)
-	} else {
-		(literal.value(), false)
-	};
+	let AssetPath {mut local_path, is_absolute, path} = parse_asset_path(token_stream);
 	
-	let assets_dir = env::var_os("CACHE_BUST_ASSETS_DIR").unwrap_or("assets".into());
+	let hash_options = HashOptions::from_env().unwrap_or_else(|err| panic!("{err}"));
 	
-	let mut local_path = PathBuf::from_str(local_path).expect("Expected a valid path");
-	let mut path = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR should exist"));
-	path.push(assets_dir);
-	path.push(&local_path);
-	
-	let mut hashed_file_name = hashed_file_name(&path).unwrap_or_else(|err| panic!("Error parsing file {path:?}: {err}"));
+	let mut hashed_file_name = hashed_file_name_with(&path, &hash_options).unwrap_or_else(|err| panic!("{err}"));
 	
 	// only revert the file_name after hashing to keep the same error reporting
 	if env::var("CACHE_BUST_SKIP_HASHING").is_ok_and(|skip_hashing| skip_hashing == "1") {
@@ -90,3 +85,124 @@ pub fn asset(token_stream: TokenStream) -> TokenStream {
 	
 	TokenTree::Literal(literal).into()
 }
+
+/// Expands to the encoded hash of a file's contents (e.g. `f04a[...]`), which can be used for
+/// ETags, `?v=` query strings or versioning a service worker.
+/// 
+/// The file is looked up the same way as by [`asset!`](asset!) and the hash is computed using the same
+/// `CACHE_BUST_HASH_ALGORITHM`, `CACHE_BUST_HASH_ENCODING` and `CACHE_BUST_HASH_LENGTH` environment
+/// variables, so it matches the hash in the name produced by [`asset!`](asset!).
+/// 
+/// If `CACHE_BUST_SKIP_HASHING` is set to `1`, the macro expands to an empty string,
+/// while still erroring if the file doesn't exist.
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use cache_bust_macro as cache_bust;
+/// use cache_bust::asset_hash;
+/// 
+/// assert_eq!(asset_hash!("images/circle.png"), "f04a632bf7de8a58d730988671a9139d6f7b3b197bbc78b6c74a4542eaa4878d");
+/// ```
+/// 
+/// Compiled with `CACHE_BUST_SKIP_HASHING=1`:
+/// ```rust,ignore
+/// assert_eq!(asset_hash!("images/circle.png"), "");
+/// ```
+#[proc_macro]
+pub fn asset_hash(token_stream: TokenStream) -> TokenStream {
+	let AssetPath {path, ..} = parse_asset_path(token_stream);
+	
+	let hash_options = HashOptions::from_env().unwrap_or_else(|err| panic!("{err}"));
+	
+	let mut hash = Hasher::new(hash_options).hash_file(&path).unwrap_or_else(|err| panic!("{err}"));
+	
+	// only clear the hash after hashing to keep the same error reporting
+	if env::var("CACHE_BUST_SKIP_HASHING").is_ok_and(|skip_hashing| skip_hashing == "1") {
+		hash.clear();
+	}
+	
+	TokenTree::Literal(Literal::string(&hash)).into()
+}
+
+/// Computes the [Subresource Integrity] string of a file (e.g. `sha384-oqVu[...]`) to be used in
+/// the `integrity` attribute of `<script>` and `<link>` elements next to [`asset!`](asset!).
+/// 
+/// The file is looked up the same way as by [`asset!`](asset!), so it's relative to the `assets`
+/// directory inside your crate or `CACHE_BUST_ASSETS_DIR` and the macro errors if it doesn't exist.
+/// 
+/// The algorithm is read from the `CACHE_BUST_INTEGRITY` environment variable (`sha256`, `sha384`
+/// or `sha512`), defaulting to `sha384`. As the integrity covers the contents of the file rather
+/// than its name, `CACHE_BUST_SKIP_HASHING` doesn't change the result.
+/// 
+/// # Examples
+/// 
+/// ```
+/// # use cache_bust_macro as cache_bust;
+/// use cache_bust::{asset, asset_integrity};
+/// 
+/// let src = asset!("/scripts/app.min.js");
+/// let integrity = asset_integrity!("/scripts/app.min.js");
+/// let script = format!(r#"<script src="{src}" integrity="{integrity}"></script>"#);
+/// assert_eq!(asset_integrity!("hello.txt"), "sha384-eaeuxwhHwkIQK4ibKYoHIIA7NAs1DNnon1dMaE1Gv7IyuS0t81b9d+TSBHxDs/ig");
+/// ```
+/// 
+/// [Subresource Integrity]: https://developer.mozilla.org/en-US/docs/Web/Security/Subresource_Integrity
+#[proc_macro]
+pub fn asset_integrity(token_stream: TokenStream) -> TokenStream {
+	let AssetPath {path, ..} = parse_asset_path(token_stream);
+	
+	let mut hash_options = HashOptions::from_env().unwrap_or_else(|err| panic!("{err}"));
+	
+	if hash_options.get_integrity().is_none() {
+		hash_options = hash_options.integrity(IntegrityAlgorithm::default());
+	}
+	
+	let integrity = Hasher::new(hash_options)
+		.hash_file_with_integrity(&path)
+		.unwrap_or_else(|err| panic!("{err}"))
+		.integrity
+		.expect("An integrity algorithm is set");
+	
+	TokenTree::Literal(Literal::string(&integrity)).into()
+}
+
+/// The path of an asset passed to a macro
+struct AssetPath {
+	/// The path relative to the assets directory
+	local_path: PathBuf,
+	/// Whether the path was given with a leading `/`
+	is_absolute: bool,
+	/// The full path of the file
+	path: PathBuf,
+}
+
+fn parse_asset_path(token_stream: TokenStream) -> AssetPath {
+	let mut iter = token_stream.into_iter();
+	let token = iter.next().expect("Expected file name as a string");
+	
+	if iter.next().is_some() {
+		panic!("Expected file name as a string");
+	}
+	
+	let literal = StringLit::try_from(token).expect("Expected file name as a string");
+	
+	let (local_path, is_absolute) = if literal.value().starts_with('/') {
+		(&literal.value()[1..], true)
+	} else {
+		(literal.value(), false)
+	};
+	
+	let assets_dir = env::var_os("CACHE_BUST_ASSETS_DIR").unwrap_or("assets".into());
+	
+	let local_path = PathBuf::from_str(local_path).expect("Expected a valid path");
+	let mut path = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR should exist"));
+	path.push(assets_dir);
+	path.push(&local_path);
+	
+	AssetPath {
+		local_path,
+		is_absolute,
+		path,
+	}
+}
@@ -9,3 +9,20 @@ fn test_macro() {
 fn test_macro_nested_dir() {
 	assert_eq!(asset!("greetings/hi.txt"), "greetings/hi.c01a4cfa25cb895cdd0bb25181ba9c1622e93895a6de6f533a7299f70d6b0cfb.txt");
 }
+
+#[test]
+fn test_macro_compound_extension() {
+	assert_eq!(asset!("scripts/app.min.js"), "scripts/app.e4f99453fffe7c06cfa40d01d83263562f85ec8ce357dc7dc0e8ce70796c1a7b.min.js");
+}
+
+#[test]
+fn test_integrity_macro() {
+	assert_eq!(asset_integrity!("hello.txt"), "sha384-eaeuxwhHwkIQK4ibKYoHIIA7NAs1DNnon1dMaE1Gv7IyuS0t81b9d+TSBHxDs/ig");
+	assert_eq!(asset_integrity!("/hello.txt"), asset_integrity!("hello.txt"));
+}
+
+#[test]
+fn test_hash_macro() {
+	assert_eq!(asset_hash!("hello.txt"), "d9014c4624844aa5bac314773d6b689ad467fa4e1d1a50a1b8a99d5a95f72ff5");
+	assert_eq!(asset_hash!("/scripts/app.min.js"), "e4f99453fffe7c06cfa40d01d83263562f85ec8ce357dc7dc0e8ce70796c1a7b");
+}